@@ -0,0 +1,132 @@
+use crate::error::Error;
+use crate::settings::Settings;
+use crate::url::Url;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// about:スキームのページを、ネットワークを使わずにブラウザ内部で生成する
+/// about:blankは空のドキュメントになる
+/// 設定は読むだけで変更しない。about:config?name=valueを開いても設定は変わらない
+pub fn load_about_page(url: &Url, settings: &Settings) -> Result<String, Error> {
+    check_about_url(url)?;
+    match url.path().as_str() {
+        "blank" => Ok(String::new()),
+        "config" => Ok(settings.about_config_html()),
+        page => Err(Error::UnexpectedInput(format!(
+            "unknown about page: about:{}",
+            page
+        ))),
+    }
+}
+
+/// about:configのフォームから送られたクエリ(about:config?name=value)を設定に反映し、ページを生成し直す
+/// Webコンテンツのリンクやリダイレクトから設定を書き換えられないように、
+/// ブラウザ自身のabout:configページからの送信に対してだけ呼び出すこと
+pub fn apply_about_config_submission(url: &Url, settings: &mut Settings) -> Result<String, Error> {
+    check_about_url(url)?;
+    if url.path() != "config" {
+        return Err(Error::UnexpectedInput(format!(
+            "not an about:config URL: about:{}",
+            url.path()
+        )));
+    }
+    let mut errors = Vec::new();
+    for (name, value) in url.search_params().iter() {
+        if let Err(e) = settings.set_by_name(name, value) {
+            errors.push(e);
+        }
+    }
+    Ok(settings.about_config_html_with_errors(&errors))
+}
+
+fn check_about_url(url: &Url) -> Result<(), Error> {
+    if url.scheme() != "about" {
+        return Err(Error::UnexpectedInput(format!(
+            "not an about URL: {}",
+            url.scheme()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    fn parse(input: &str) -> Url {
+        Url::parse(input).expect("failed to parse url")
+    }
+
+    #[test]
+    fn test_about_blank() {
+        let settings = Settings::new();
+        assert_eq!(
+            load_about_page(&parse("about:blank"), &settings),
+            Ok("".to_string())
+        );
+    }
+
+    #[test]
+    fn test_about_config() {
+        let settings = Settings::new();
+        assert_eq!(
+            load_about_page(&parse("about:config"), &settings),
+            Ok(settings.about_config_html())
+        );
+    }
+
+    #[test]
+    fn test_about_config_query_is_read_only() {
+        // ページを読み込むだけでは設定は変わらない
+        let settings = Settings::new();
+        let html = load_about_page(
+            &parse("about:config?javascript.enabled=false&layout.zoom.default=150"),
+            &settings,
+        )
+        .expect("failed to load about:config");
+        assert!(settings.javascript_enabled());
+        assert_eq!(settings.default_zoom(), 100);
+        assert_eq!(html, settings.about_config_html());
+    }
+
+    #[test]
+    fn test_about_config_submit() {
+        let mut settings = Settings::new();
+        let html = apply_about_config_submission(
+            &parse("about:config?javascript.enabled=false&layout.zoom.default=150"),
+            &mut settings,
+        )
+        .expect("failed to load about:config");
+        assert!(!settings.javascript_enabled());
+        assert_eq!(settings.default_zoom(), 150);
+        assert_eq!(html, settings.about_config_html());
+    }
+
+    #[test]
+    fn test_about_config_submit_error() {
+        let mut settings = Settings::new();
+        let html = apply_about_config_submission(
+            &parse("about:config?layout.zoom.default=abc&image.loading.enabled=false"),
+            &mut settings,
+        )
+        .expect("failed to load about:config");
+        // 正しい値だけが反映され、エラーはページに表示される
+        assert_eq!(settings.default_zoom(), 100);
+        assert!(!settings.image_loading_enabled());
+        assert!(html.contains("layout.zoom.default expects an integer: abc"));
+    }
+
+    #[test]
+    fn test_unknown_about_page() {
+        let settings = Settings::new();
+        assert!(load_about_page(&parse("about:unknown"), &settings).is_err());
+        assert!(load_about_page(&parse("http://example.com"), &settings).is_err());
+
+        let mut settings = Settings::new();
+        let url = parse("about:blank?javascript.enabled=false");
+        assert!(apply_about_config_submission(&url, &mut settings).is_err());
+        assert!(settings.javascript_enabled());
+    }
+}
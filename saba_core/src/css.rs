@@ -0,0 +1,5 @@
+pub mod color;
+pub mod font;
+pub mod length;
+pub mod media;
+pub mod selector;
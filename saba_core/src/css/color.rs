@@ -0,0 +1,462 @@
+use crate::css::length::split_number;
+use crate::error::Error;
+use alloc::format;
+use alloc::vec::Vec;
+use core::f64::consts::PI;
+use core::fmt;
+
+/// sRGBの色。各成分とアルファ値は0から255
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgba {
+    r: u8,
+    g: u8,
+    b: u8,
+    a: u8,
+}
+
+/// https://www.w3.org/TR/css-color-4/#color-syntax
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Rgba(Rgba),
+    /// 要素のcolorプロパティの値を使う
+    CurrentColor,
+}
+
+impl Rgba {
+    pub const TRANSPARENT: Self = Self::new(0, 0, 0, 0);
+    pub const BLACK: Self = Self::new(0, 0, 0, 255);
+
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// 0xRRGGBBの形式の不透明な色
+    pub const fn from_rgb(rgb: u32) -> Self {
+        Self::new((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8, 255)
+    }
+
+    pub fn r(&self) -> u8 {
+        self.r
+    }
+    pub fn g(&self) -> u8 {
+        self.g
+    }
+    pub fn b(&self) -> u8 {
+        self.b
+    }
+    pub fn a(&self) -> u8 {
+        self.a
+    }
+}
+
+impl fmt::Display for Rgba {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)?;
+        if self.a != 255 {
+            write!(f, "{:02x}", self.a)?;
+        }
+        Ok(())
+    }
+}
+
+impl Color {
+    /// "#f00"、"rgb(255 0 0 / 50%)"、"hsl(120, 100%, 50%)"、"red"、"currentColor"などをパースする
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        let input = input.trim();
+        let invalid = || Error::UnexpectedInput(format!("invalid color: {}", input));
+
+        if let Some(hex) = input.strip_prefix('#') {
+            return parse_hex(hex).map(Color::Rgba).ok_or_else(invalid);
+        }
+
+        let lower = input.to_ascii_lowercase();
+        if let Some((name, arguments)) = lower.split_once('(') {
+            let arguments = arguments.strip_suffix(')').ok_or_else(invalid)?;
+            let rgba = match name.trim_end() {
+                "rgb" | "rgba" => parse_rgb(arguments),
+                "hsl" | "hsla" => parse_hsl(arguments),
+                _ => None,
+            };
+            return rgba.map(Color::Rgba).ok_or_else(invalid);
+        }
+
+        match lower.as_str() {
+            "currentcolor" => Ok(Color::CurrentColor),
+            "transparent" => Ok(Color::Rgba(Rgba::TRANSPARENT)),
+            name => match NAMED_COLORS.binary_search_by(|(n, _)| (*n).cmp(name)) {
+                Ok(index) => Ok(Color::Rgba(Rgba::from_rgb(NAMED_COLORS[index].1))),
+                Err(_) => Err(invalid()),
+            },
+        }
+    }
+
+    /// currentColorを要素のcolorプロパティの値で置き換える
+    pub fn resolve(&self, current_color: Rgba) -> Rgba {
+        match self {
+            Color::Rgba(rgba) => *rgba,
+            Color::CurrentColor => current_color,
+        }
+    }
+}
+
+/// #rgb、#rgba、#rrggbb、#rrggbbaa
+fn parse_hex(hex: &str) -> Option<Rgba> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok();
+    let pair = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    match hex.len() {
+        3 | 4 => {
+            // #abcは#aabbccの省略形
+            let a = if hex.len() == 4 { digit(3)? * 17 } else { 255 };
+            Some(Rgba::new(digit(0)? * 17, digit(1)? * 17, digit(2)? * 17, a))
+        }
+        6 | 8 => {
+            let a = if hex.len() == 8 { pair(6)? } else { 255 };
+            Some(Rgba::new(pair(0)?, pair(2)?, pair(4)?, a))
+        }
+        _ => None,
+    }
+}
+
+/// "255, 0, 0, 0.5"のようなカンマ区切りの古い構文と、"255 0 0 / 50%"のような空白区切りの構文の両方を受け付ける
+/// 戻り値は色の3成分とアルファ値
+fn split_arguments(arguments: &str) -> Option<(Vec<&str>, Option<&str>)> {
+    if arguments.contains(',') {
+        let mut values: Vec<&str> = arguments.split(',').map(|v| v.trim()).collect();
+        let alpha = match values.len() {
+            3 => None,
+            4 => values.pop(),
+            _ => return None,
+        };
+        return Some((values, alpha));
+    }
+
+    let (values, alpha) = match arguments.split_once('/') {
+        Some((values, alpha)) => (values, Some(alpha.trim())),
+        None => (arguments, None),
+    };
+    let values: Vec<&str> = values.split_ascii_whitespace().collect();
+    if values.len() != 3 {
+        return None;
+    }
+    Some((values, alpha))
+}
+
+/// 数値と単位に分ける。数値でなければNone
+fn parse_number(input: &str) -> Option<(f64, &str)> {
+    let (number, unit) = split_number(input);
+    Some((number.parse().ok()?, unit))
+}
+
+/// 0から1の値を0から255に丸める
+fn to_u8(value: f64) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0 + 0.5) as u8
+}
+
+fn parse_alpha(alpha: Option<&str>) -> Option<u8> {
+    let alpha = match alpha {
+        Some(alpha) => alpha,
+        None => return Some(255),
+    };
+    match parse_number(alpha)? {
+        (value, "") => Some(to_u8(value)),
+        (value, "%") => Some(to_u8(value / 100.0)),
+        _ => None,
+    }
+}
+
+/// https://www.w3.org/TR/css-color-4/#rgb-functions
+fn parse_rgb(arguments: &str) -> Option<Rgba> {
+    let (values, alpha) = split_arguments(arguments)?;
+    let mut channels = [0; 3];
+    let mut percentages = 0;
+    for (channel, value) in channels.iter_mut().zip(values) {
+        *channel = match parse_number(value)? {
+            (value, "") => to_u8(value / 255.0),
+            (value, "%") => {
+                percentages += 1;
+                to_u8(value / 100.0)
+            }
+            _ => return None,
+        };
+    }
+    // 数値とパーセントを混ぜることはできない
+    if percentages != 0 && percentages != 3 {
+        return None;
+    }
+    Some(Rgba::new(
+        channels[0],
+        channels[1],
+        channels[2],
+        parse_alpha(alpha)?,
+    ))
+}
+
+/// https://www.w3.org/TR/css-color-4/#the-hsl-notation
+fn parse_hsl(arguments: &str) -> Option<Rgba> {
+    let (values, alpha) = split_arguments(arguments)?;
+    let hue = match parse_number(values[0])? {
+        (value, "") | (value, "deg") => value,
+        (value, "grad") => value * 0.9,
+        (value, "rad") => value * 180.0 / PI,
+        (value, "turn") => value * 360.0,
+        _ => return None,
+    };
+    let percentage = |value: &str| match parse_number(value)? {
+        (value, "%") | (value, "") => Some((value / 100.0).clamp(0.0, 1.0)),
+        _ => None,
+    };
+    let saturation = percentage(values[1])?;
+    let lightness = percentage(values[2])?;
+
+    let (r, g, b) = hsl_to_rgb(hue, saturation, lightness);
+    Some(Rgba::new(to_u8(r), to_u8(g), to_u8(b), parse_alpha(alpha)?))
+}
+
+/// https://www.w3.org/TR/css-color-4/#hsl-to-rgb
+/// hueは度、saturationとlightnessは0から1
+fn hsl_to_rgb(hue: f64, saturation: f64, lightness: f64) -> (f64, f64, f64) {
+    let mut hue = hue % 360.0;
+    if hue < 0.0 {
+        hue += 360.0;
+    }
+    let f = |n: f64| {
+        let k = (n + hue / 30.0) % 12.0;
+        let a = saturation * lightness.min(1.0 - lightness);
+        lightness - a * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+    };
+    (f(0.0), f(8.0), f(4.0))
+}
+
+/// https://www.w3.org/TR/css-color-4/#named-colors
+/// 名前の順に並べてあるので、二分探索できる
+const NAMED_COLORS: [(&str, u32); 148] = [
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    fn rgba(input: &str) -> Rgba {
+        match Color::parse(input).expect("failed to parse color") {
+            Color::Rgba(rgba) => rgba,
+            Color::CurrentColor => panic!("unexpected currentColor"),
+        }
+    }
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(rgba("#f00"), Rgba::new(255, 0, 0, 255));
+        assert_eq!(rgba("#F008"), Rgba::new(255, 0, 0, 0x88));
+        assert_eq!(rgba("#1a2B3c"), Rgba::new(0x1a, 0x2b, 0x3c, 255));
+        assert_eq!(rgba("#1a2b3c80"), Rgba::new(0x1a, 0x2b, 0x3c, 0x80));
+        assert!(Color::parse("#12").is_err());
+        assert!(Color::parse("#12345").is_err());
+        assert!(Color::parse("#ggg").is_err());
+    }
+
+    #[test]
+    fn test_rgb() {
+        assert_eq!(rgba("rgb(255, 128, 0)"), Rgba::new(255, 128, 0, 255));
+        assert_eq!(rgba("RGBA(255,128,0,0.5)"), Rgba::new(255, 128, 0, 128));
+        assert_eq!(rgba("rgb(100%, 50%, 0%)"), Rgba::new(255, 128, 0, 255));
+        assert_eq!(rgba("rgb(255 128 0 / 25%)"), Rgba::new(255, 128, 0, 64));
+        assert_eq!(rgba("rgba(300, -5, 0)"), Rgba::new(255, 0, 0, 255));
+        assert!(Color::parse("rgb(255, 50%, 0)").is_err());
+        assert!(Color::parse("rgb(255, 0)").is_err());
+        assert!(Color::parse("rgb(255 0 0").is_err());
+        assert!(Color::parse("rgb(1px, 0, 0)").is_err());
+    }
+
+    #[test]
+    fn test_hsl() {
+        assert_eq!(rgba("hsl(0, 100%, 50%)"), Rgba::new(255, 0, 0, 255));
+        assert_eq!(rgba("hsl(120deg 100% 25%)"), Rgba::new(0, 128, 0, 255));
+        assert_eq!(rgba("hsl(0.5turn, 100%, 50%)"), Rgba::new(0, 255, 255, 255));
+        assert_eq!(rgba("hsl(-120, 100%, 50%)"), Rgba::new(0, 0, 255, 255));
+        assert_eq!(rgba("hsla(0, 0%, 100%, 0)"), Rgba::new(255, 255, 255, 0));
+        assert_eq!(rgba("hsl(270 50% 40%)"), Rgba::new(102, 51, 153, 255));
+        assert!(Color::parse("hsl(0, 100%)").is_err());
+    }
+
+    #[test]
+    fn test_named() {
+        assert_eq!(rgba("red"), Rgba::new(255, 0, 0, 255));
+        assert_eq!(rgba("RebeccaPurple"), Rgba::from_rgb(0x663399));
+        assert_eq!(rgba("aliceblue"), Rgba::from_rgb(0xf0f8ff));
+        assert_eq!(rgba("yellowgreen"), Rgba::from_rgb(0x9acd32));
+        assert_eq!(rgba("transparent"), Rgba::TRANSPARENT);
+        assert!(Color::parse("notacolor").is_err());
+        assert!(NAMED_COLORS.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn test_current_color() {
+        let color = Color::parse("currentColor").expect("failed to parse color");
+        assert_eq!(color, Color::CurrentColor);
+        assert_eq!(color.resolve(Rgba::BLACK), Rgba::BLACK);
+        assert_eq!(
+            Color::parse("blue")
+                .expect("failed to parse color")
+                .resolve(Rgba::BLACK),
+            Rgba::from_rgb(0x0000ff)
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Rgba::new(255, 0, 16, 255).to_string(), "#ff0010");
+        assert_eq!(Rgba::new(255, 0, 16, 128).to_string(), "#ff001080");
+    }
+}
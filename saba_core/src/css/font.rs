@@ -0,0 +1,459 @@
+use crate::css::length::FontSize;
+use crate::css::length::Length;
+use crate::css::length::LengthContext;
+use crate::css::length::split_number;
+use crate::error::Error;
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+/// line-height: normalのときにfont-sizeに掛ける倍率
+const NORMAL_LINE_HEIGHT: f64 = 1.2;
+
+/// https://www.w3.org/TR/css-fonts-4/#font-style-prop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FontStyle {
+    Normal,
+    Italic,
+    Oblique,
+}
+
+/// https://www.w3.org/TR/css-fonts-4/#font-weight-prop
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FontWeight {
+    /// 1から1000までの太さ。normalは400、boldは700
+    Absolute(f64),
+    /// 親要素より太くする
+    Bolder,
+    /// 親要素より細くする
+    Lighter,
+}
+
+/// https://www.w3.org/TR/css-fonts-4/#generic-font-families
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenericFamily {
+    Serif,
+    SansSerif,
+    Monospace,
+    Cursive,
+    Fantasy,
+    SystemUi,
+}
+
+/// font-familyに並べる1つのファミリー
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FontFamily {
+    /// "Noto Sans JP"のような具体的なフォント名
+    Named(String),
+    Generic(GenericFamily),
+}
+
+/// https://www.w3.org/TR/css-inline-3/#line-height-property
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineHeight {
+    Normal,
+    /// font-sizeに対する倍率
+    Number(f64),
+    Length(Length),
+}
+
+/// "italic bold 16px/1.4 sans-serif"のようなfontショートハンドの値
+/// 省略された値は初期値になる
+/// https://www.w3.org/TR/css-fonts-4/#font-prop
+#[derive(Debug, Clone, PartialEq)]
+pub struct Font {
+    style: FontStyle,
+    weight: FontWeight,
+    size: FontSize,
+    line_height: LineHeight,
+    families: Vec<FontFamily>,
+}
+
+/// serifやsans-serifなどの総称ファミリーを、埋め込み先の環境にある実際のフォント名に対応づけるフック
+pub trait GenericFamilyResolver: Send + Sync {
+    /// 対応するフォントがなければNoneを返す
+    fn resolve(&self, family: GenericFamily) -> Option<String>;
+}
+
+impl FontStyle {
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        match input.trim().to_ascii_lowercase().as_str() {
+            "normal" => Ok(FontStyle::Normal),
+            "italic" => Ok(FontStyle::Italic),
+            "oblique" => Ok(FontStyle::Oblique),
+            _ => Err(Error::UnexpectedInput(format!(
+                "invalid font-style: {}",
+                input
+            ))),
+        }
+    }
+}
+
+impl FontWeight {
+    pub const NORMAL: Self = FontWeight::Absolute(400.0);
+    pub const BOLD: Self = FontWeight::Absolute(700.0);
+
+    /// "normal"、"bold"、"bolder"、"lighter"、"600"などをパースする
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        let input = input.trim();
+        match input.to_ascii_lowercase().as_str() {
+            "normal" => return Ok(Self::NORMAL),
+            "bold" => return Ok(Self::BOLD),
+            "bolder" => return Ok(FontWeight::Bolder),
+            "lighter" => return Ok(FontWeight::Lighter),
+            _ => {}
+        }
+
+        let (number, unit) = split_number(input);
+        match number.parse::<f64>() {
+            Ok(weight) if unit.is_empty() && (1.0..=1000.0).contains(&weight) => {
+                Ok(FontWeight::Absolute(weight))
+            }
+            _ => Err(Error::UnexpectedInput(format!(
+                "invalid font-weight: {}",
+                input
+            ))),
+        }
+    }
+
+    /// font-weightの計算値を求める。bolderとlighterは親要素の太さから決まる
+    /// https://www.w3.org/TR/css-fonts-4/#relative-weights
+    pub fn compute(&self, parent_weight: f64) -> f64 {
+        match self {
+            FontWeight::Absolute(weight) => *weight,
+            FontWeight::Bolder => match parent_weight {
+                w if w < 350.0 => 400.0,
+                w if w < 550.0 => 700.0,
+                w if w < 900.0 => 900.0,
+                w => w,
+            },
+            FontWeight::Lighter => match parent_weight {
+                w if w < 100.0 => w,
+                w if w < 550.0 => 100.0,
+                w if w < 750.0 => 400.0,
+                _ => 700.0,
+            },
+        }
+    }
+}
+
+impl GenericFamily {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "serif" => Some(GenericFamily::Serif),
+            "sans-serif" => Some(GenericFamily::SansSerif),
+            "monospace" => Some(GenericFamily::Monospace),
+            "cursive" => Some(GenericFamily::Cursive),
+            "fantasy" => Some(GenericFamily::Fantasy),
+            "system-ui" => Some(GenericFamily::SystemUi),
+            _ => None,
+        }
+    }
+}
+
+impl FontFamily {
+    /// "\"Noto Sans JP\", Arial, sans-serif"のようなカンマ区切りのリストをパースする
+    /// 引用符で囲まれていない名前は、空白を1つにまとめる
+    pub fn parse_list(input: &str) -> Result<Vec<Self>, Error> {
+        let invalid = || Error::UnexpectedInput(format!("invalid font-family: {}", input.trim()));
+        let mut families = Vec::new();
+        let mut rest = input.trim();
+        loop {
+            let (family, remaining) = match rest.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let body = &rest[1..];
+                    let end = body.find(quote).ok_or_else(invalid)?;
+                    (
+                        FontFamily::Named(body[..end].to_string()),
+                        body[end + 1..].trim_start(),
+                    )
+                }
+                _ => {
+                    let end = rest.find(',').unwrap_or(rest.len());
+                    let words: Vec<&str> = rest[..end].split_whitespace().collect();
+                    let family = match words.as_slice() {
+                        [] => return Err(invalid()),
+                        // 総称ファミリーのキーワードは、引用符で囲まなければ1語のときだけ
+                        [word] => match GenericFamily::from_name(&word.to_ascii_lowercase()) {
+                            Some(generic) => FontFamily::Generic(generic),
+                            None => FontFamily::Named(word.to_string()),
+                        },
+                        words => FontFamily::Named(words.join(" ")),
+                    };
+                    (family, &rest[end..])
+                }
+            };
+            families.push(family);
+            if remaining.is_empty() {
+                return Ok(families);
+            }
+            rest = remaining
+                .strip_prefix(',')
+                .ok_or_else(invalid)?
+                .trim_start();
+        }
+    }
+}
+
+impl LineHeight {
+    /// "normal"、"1.5"、"24px"、"150%"などをパースする
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        let input = input.trim();
+        let invalid = || Error::UnexpectedInput(format!("invalid line-height: {}", input));
+        if input.eq_ignore_ascii_case("normal") {
+            return Ok(LineHeight::Normal);
+        }
+
+        let (number, unit) = split_number(input);
+        if unit.is_empty() {
+            return match number.parse::<f64>() {
+                Ok(number) if number >= 0.0 => Ok(LineHeight::Number(number)),
+                _ => Err(invalid()),
+            };
+        }
+        match Length::parse(input) {
+            Ok(length) if !length.is_negative() => Ok(LineHeight::Length(length)),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// 行の高さをピクセルで求める
+    /// contextのfont-sizeには、計算済みの要素自身のfont-sizeを渡す。%もそのfont-sizeを基準にする
+    pub fn to_px(&self, context: &LengthContext) -> f64 {
+        match self {
+            LineHeight::Normal => context.font_size() * NORMAL_LINE_HEIGHT,
+            LineHeight::Number(number) => context.font_size() * number,
+            LineHeight::Length(length) => length.to_px(context, context.font_size()),
+        }
+    }
+}
+
+impl Font {
+    /// fontショートハンドをパースする
+    /// font-sizeとfont-familyは必須で、その前にfont-styleとfont-weightを任意の順で書ける
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        let invalid = || Error::UnexpectedInput(format!("invalid font: {}", input.trim()));
+        let mut style = None;
+        let mut weight = None;
+        let mut rest = input.trim();
+
+        // font-sizeが見つかるまで、font-styleとfont-weightを読む
+        let size_token = loop {
+            let (token, remaining) = split_token(rest);
+            if token.is_empty() {
+                return Err(invalid());
+            }
+            rest = remaining;
+            if token.eq_ignore_ascii_case("normal") {
+                continue;
+            }
+            if style.is_none()
+                && let Ok(value) = FontStyle::parse(token)
+            {
+                style = Some(value);
+                continue;
+            }
+            if weight.is_none()
+                && let Ok(value) = FontWeight::parse(token)
+            {
+                weight = Some(value);
+                continue;
+            }
+            break token;
+        };
+
+        // "16px/1.4"と"16px / 1.4"のどちらの書き方も受け付ける
+        let (size, mut line_height) = match size_token.split_once('/') {
+            Some((size, line_height)) => (size, Some(line_height)),
+            None => (size_token, None),
+        };
+        if line_height.is_none()
+            && let Some(remaining) = rest.strip_prefix('/')
+        {
+            line_height = Some("");
+            rest = remaining.trim_start();
+        }
+        if line_height == Some("") {
+            let (token, remaining) = split_token(rest);
+            line_height = Some(token);
+            rest = remaining;
+        }
+
+        Ok(Self {
+            style: style.unwrap_or(FontStyle::Normal),
+            weight: weight.unwrap_or(FontWeight::NORMAL),
+            size: FontSize::parse(size).map_err(|_| invalid())?,
+            line_height: match line_height {
+                Some(line_height) => LineHeight::parse(line_height).map_err(|_| invalid())?,
+                None => LineHeight::Normal,
+            },
+            families: FontFamily::parse_list(rest).map_err(|_| invalid())?,
+        })
+    }
+
+    pub fn style(&self) -> FontStyle {
+        self.style
+    }
+    pub fn weight(&self) -> FontWeight {
+        self.weight
+    }
+    pub fn size(&self) -> FontSize {
+        self.size
+    }
+    pub fn line_height(&self) -> LineHeight {
+        self.line_height
+    }
+    pub fn families(&self) -> Vec<FontFamily> {
+        self.families.clone()
+    }
+
+    /// フォールバックの順に、実際に探すフォント名を返す
+    /// 総称ファミリーはresolverで置き換え、対応するフォントがなければ飛ばす
+    pub fn family_names(&self, resolver: &dyn GenericFamilyResolver) -> Vec<String> {
+        self.families
+            .iter()
+            .filter_map(|family| match family {
+                FontFamily::Named(name) => Some(name.clone()),
+                FontFamily::Generic(generic) => resolver.resolve(*generic),
+            })
+            .collect()
+    }
+}
+
+/// 先頭の空白区切りの1語と、その後ろの残りに分ける
+fn split_token(input: &str) -> (&str, &str) {
+    match input.find(char::is_whitespace) {
+        Some(end) => (&input[..end], input[end..].trim_start()),
+        None => (input, ""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::css::length::AbsoluteSize;
+    use alloc::vec;
+
+    struct TestResolver;
+
+    impl GenericFamilyResolver for TestResolver {
+        fn resolve(&self, family: GenericFamily) -> Option<String> {
+            match family {
+                GenericFamily::SansSerif => Some("Noto Sans".to_string()),
+                GenericFamily::Monospace => Some("Noto Sans Mono".to_string()),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_font_weight() {
+        assert_eq!(FontWeight::parse("normal"), Ok(FontWeight::Absolute(400.0)));
+        assert_eq!(FontWeight::parse("BOLD"), Ok(FontWeight::Absolute(700.0)));
+        assert_eq!(FontWeight::parse("550"), Ok(FontWeight::Absolute(550.0)));
+        assert_eq!(FontWeight::parse("lighter"), Ok(FontWeight::Lighter));
+        assert!(FontWeight::parse("0").is_err());
+        assert!(FontWeight::parse("1001").is_err());
+        assert!(FontWeight::parse("700px").is_err());
+        assert!(FontWeight::parse("heavy").is_err());
+    }
+
+    #[test]
+    fn test_relative_font_weight() {
+        assert_eq!(FontWeight::Bolder.compute(50.0), 400.0);
+        assert_eq!(FontWeight::Bolder.compute(400.0), 700.0);
+        assert_eq!(FontWeight::Bolder.compute(600.0), 900.0);
+        assert_eq!(FontWeight::Bolder.compute(950.0), 950.0);
+        assert_eq!(FontWeight::Lighter.compute(50.0), 50.0);
+        assert_eq!(FontWeight::Lighter.compute(400.0), 100.0);
+        assert_eq!(FontWeight::Lighter.compute(700.0), 400.0);
+        assert_eq!(FontWeight::Lighter.compute(900.0), 700.0);
+        assert_eq!(FontWeight::BOLD.compute(100.0), 700.0);
+    }
+
+    #[test]
+    fn test_font_family_list() {
+        assert_eq!(
+            FontFamily::parse_list("\"Noto Sans JP\", Yu  Gothic,SANS-SERIF, 'serif'"),
+            Ok(vec![
+                FontFamily::Named("Noto Sans JP".to_string()),
+                FontFamily::Named("Yu Gothic".to_string()),
+                FontFamily::Generic(GenericFamily::SansSerif),
+                FontFamily::Named("serif".to_string()),
+            ])
+        );
+        assert!(FontFamily::parse_list("").is_err());
+        assert!(FontFamily::parse_list("Arial,").is_err());
+        assert!(FontFamily::parse_list("\"Arial").is_err());
+        assert!(FontFamily::parse_list("\"Arial\" serif").is_err());
+    }
+
+    #[test]
+    fn test_line_height() {
+        let context = LengthContext::new(20.0, 16.0);
+        let to_px = |input: &str| {
+            LineHeight::parse(input)
+                .expect("failed to parse line-height")
+                .to_px(&context)
+        };
+        assert_eq!(to_px("normal"), 24.0);
+        assert_eq!(to_px("1.5"), 30.0);
+        assert_eq!(to_px("150%"), 30.0);
+        assert_eq!(to_px("2em"), 40.0);
+        assert_eq!(to_px("18px"), 18.0);
+        assert!(LineHeight::parse("-1").is_err());
+        assert!(LineHeight::parse("-2px").is_err());
+    }
+
+    #[test]
+    fn test_font_shorthand() {
+        let font = Font::parse("italic bold 16px/1.4 sans-serif").expect("failed to parse font");
+        assert_eq!(font.style(), FontStyle::Italic);
+        assert_eq!(font.weight(), FontWeight::Absolute(700.0));
+        assert_eq!(font.size(), FontSize::Length(Length::Px(16.0)));
+        assert_eq!(font.line_height(), LineHeight::Number(1.4));
+        assert_eq!(
+            font.families(),
+            vec![FontFamily::Generic(GenericFamily::SansSerif)]
+        );
+
+        // 省略した値は初期値になり、font-styleとfont-weightの順番は問わない
+        let font = Font::parse("600 oblique large / 2em \"Noto Serif\", serif")
+            .expect("failed to parse font");
+        assert_eq!(font.style(), FontStyle::Oblique);
+        assert_eq!(font.weight(), FontWeight::Absolute(600.0));
+        assert_eq!(font.size(), FontSize::Absolute(AbsoluteSize::Large));
+        assert_eq!(font.line_height(), LineHeight::Length(Length::Em(2.0)));
+
+        let font = Font::parse("normal smaller monospace").expect("failed to parse font");
+        assert_eq!(font.style(), FontStyle::Normal);
+        assert_eq!(font.weight(), FontWeight::NORMAL);
+        assert_eq!(font.size(), FontSize::Smaller);
+        assert_eq!(font.line_height(), LineHeight::Normal);
+    }
+
+    #[test]
+    fn test_font_shorthand_invalid() {
+        // font-sizeとfont-familyは省略できない
+        assert!(Font::parse("bold sans-serif").is_err());
+        assert!(Font::parse("16px").is_err());
+        assert!(Font::parse("italic italic 16px serif").is_err());
+        assert!(Font::parse("16px/ serif").is_err());
+        assert!(Font::parse("").is_err());
+    }
+
+    #[test]
+    fn test_family_names() {
+        let font = Font::parse("12px \"Noto Sans JP\", cursive, sans-serif, monospace")
+            .expect("failed to parse font");
+        assert_eq!(
+            font.family_names(&TestResolver),
+            vec![
+                "Noto Sans JP".to_string(),
+                "Noto Sans".to_string(),
+                "Noto Sans Mono".to_string(),
+            ]
+        );
+    }
+}
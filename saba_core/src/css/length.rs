@@ -0,0 +1,477 @@
+use crate::error::Error;
+use alloc::format;
+use core::fmt;
+
+/// https://www.w3.org/TR/css-values-4/#absolute-lengths
+const CSS_PIXELS_PER_INCH: f64 = 96.0;
+
+/// font-sizeのmediumの大きさ (ピクセル)
+pub const MEDIUM_FONT_SIZE: f64 = 16.0;
+
+/// larger、smallerで親要素のfont-sizeに掛ける倍率
+const RELATIVE_FONT_SIZE_RATIO: f64 = 1.2;
+
+/// https://www.w3.org/TR/css-values-4/#lengths
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Length {
+    Px(f64),
+    /// 要素のfont-sizeに対する倍率 (font-size自身では親要素のfont-size)
+    Em(f64),
+    /// ルート要素のfont-sizeに対する倍率
+    Rem(f64),
+    /// 基準となる長さに対する割合 (50%ならPercent(50.0))
+    Percent(f64),
+    /// ビューポートの幅の1%
+    Vw(f64),
+    /// ビューポートの高さの1%
+    Vh(f64),
+    Vmin(f64),
+    Vmax(f64),
+    /// 1インチの72分の1
+    Pt(f64),
+    /// 1インチの6分の1
+    Pc(f64),
+    In(f64),
+    Cm(f64),
+    Mm(f64),
+    /// 4分の1ミリメートル
+    Q(f64),
+}
+
+/// font-sizeプロパティの値
+/// https://www.w3.org/TR/css-fonts-4/#font-size-prop
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FontSize {
+    /// xx-smallからxxx-largeまでのキーワード
+    Absolute(AbsoluteSize),
+    /// 親要素のfont-sizeより1段階大きい
+    Larger,
+    /// 親要素のfont-sizeより1段階小さい
+    Smaller,
+    Length(Length),
+}
+
+/// https://www.w3.org/TR/css-fonts-4/#absolute-size-mapping
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AbsoluteSize {
+    XxSmall,
+    XSmall,
+    Small,
+    Medium,
+    Large,
+    XLarge,
+    XxLarge,
+    XxxLarge,
+}
+
+/// 相対的な長さをピクセルに解決するときの基準となる値
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LengthContext {
+    font_size: f64,
+    root_font_size: f64,
+    viewport_width: f64,
+    viewport_height: f64,
+    dpi: f64,
+}
+
+impl LengthContext {
+    /// font-sizeは計算済みの要素のfont-size、root_font_sizeはルート要素のfont-size (どちらもピクセル)
+    pub fn new(font_size: f64, root_font_size: f64) -> Self {
+        Self {
+            font_size,
+            root_font_size,
+            viewport_width: 0.0,
+            viewport_height: 0.0,
+            dpi: CSS_PIXELS_PER_INCH,
+        }
+    }
+
+    /// vwやvhの基準となるビューポートの大きさ (ピクセル)
+    pub fn with_viewport(mut self, width: f64, height: f64) -> Self {
+        self.viewport_width = width;
+        self.viewport_height = height;
+        self
+    }
+
+    /// 1インチあたりのピクセル数。CSSの定義では96だが、実際の画面に合わせて変えられる
+    pub fn with_dpi(mut self, dpi: f64) -> Self {
+        self.dpi = dpi;
+        self
+    }
+
+    pub fn font_size(&self) -> f64 {
+        self.font_size
+    }
+    pub fn root_font_size(&self) -> f64 {
+        self.root_font_size
+    }
+    pub fn viewport_width(&self) -> f64 {
+        self.viewport_width
+    }
+    pub fn viewport_height(&self) -> f64 {
+        self.viewport_height
+    }
+    pub fn dpi(&self) -> f64 {
+        self.dpi
+    }
+}
+
+impl Length {
+    /// "10px"、"1.5em"、"-2rem"、"50%"、"0"などをパースする
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        let input = input.trim();
+        let (number, unit) = split_number(input);
+        let value: f64 = match number.parse() {
+            Ok(value) => value,
+            Err(_) => {
+                return Err(Error::UnexpectedInput(format!("invalid length: {}", input)));
+            }
+        };
+
+        match unit.to_ascii_lowercase().as_str() {
+            "px" => Ok(Length::Px(value)),
+            "em" => Ok(Length::Em(value)),
+            "rem" => Ok(Length::Rem(value)),
+            "%" => Ok(Length::Percent(value)),
+            "vw" => Ok(Length::Vw(value)),
+            "vh" => Ok(Length::Vh(value)),
+            "vmin" => Ok(Length::Vmin(value)),
+            "vmax" => Ok(Length::Vmax(value)),
+            "pt" => Ok(Length::Pt(value)),
+            "pc" => Ok(Length::Pc(value)),
+            "in" => Ok(Length::In(value)),
+            "cm" => Ok(Length::Cm(value)),
+            "mm" => Ok(Length::Mm(value)),
+            "q" => Ok(Length::Q(value)),
+            // 0だけは単位を省略できる
+            "" if value == 0.0 => Ok(Length::Px(0.0)),
+            _ => Err(Error::UnexpectedInput(format!(
+                "unsupported length unit: {}",
+                input
+            ))),
+        }
+    }
+
+    /// 使用値をピクセルで求める
+    /// percentage_basisは%の基準で、widthやpaddingなら包含ブロックの幅を渡す
+    pub fn to_px(&self, context: &LengthContext, percentage_basis: f64) -> f64 {
+        match self {
+            Length::Px(px) => *px,
+            Length::Em(em) => em * context.font_size,
+            Length::Rem(rem) => rem * context.root_font_size,
+            Length::Percent(percent) => percent * percentage_basis / 100.0,
+            Length::Vw(vw) => vw * context.viewport_width / 100.0,
+            Length::Vh(vh) => vh * context.viewport_height / 100.0,
+            Length::Vmin(vmin) => {
+                vmin * context.viewport_width.min(context.viewport_height) / 100.0
+            }
+            Length::Vmax(vmax) => {
+                vmax * context.viewport_width.max(context.viewport_height) / 100.0
+            }
+            Length::Pt(pt) => pt * context.dpi / 72.0,
+            Length::Pc(pc) => pc * context.dpi / 6.0,
+            Length::In(inches) => inches * context.dpi,
+            Length::Cm(cm) => cm * context.dpi / 2.54,
+            Length::Mm(mm) => mm * context.dpi / 25.4,
+            Length::Q(q) => q * context.dpi / 101.6,
+        }
+    }
+
+    /// font-sizeプロパティの計算値をピクセルで求める
+    /// font-size自身のemと%は親要素のfont-sizeを基準にする
+    /// remやvw、ptなどはcontextのルート要素のfont-size、ビューポート、DPIで解決する
+    /// ルート要素ではparent_font_sizeとcontextのroot_font_sizeに初期値(16px)を渡す
+    pub fn compute_font_size(&self, parent_font_size: f64, context: &LengthContext) -> f64 {
+        let context = LengthContext {
+            font_size: parent_font_size,
+            ..*context
+        };
+        self.to_px(&context, parent_font_size)
+    }
+
+    /// 値を解決するのに、包含ブロックの大きさがわかるまで待つ必要があるか
+    pub fn is_percentage(&self) -> bool {
+        matches!(self, Length::Percent(_))
+    }
+
+    /// font-sizeやline-heightのように、負の値を使えないプロパティのための判定
+    pub(crate) fn is_negative(&self) -> bool {
+        match self {
+            Length::Px(v)
+            | Length::Em(v)
+            | Length::Rem(v)
+            | Length::Percent(v)
+            | Length::Vw(v)
+            | Length::Vh(v)
+            | Length::Vmin(v)
+            | Length::Vmax(v)
+            | Length::Pt(v)
+            | Length::Pc(v)
+            | Length::In(v)
+            | Length::Cm(v)
+            | Length::Mm(v)
+            | Length::Q(v) => *v < 0.0,
+        }
+    }
+}
+
+impl AbsoluteSize {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "xx-small" => Some(AbsoluteSize::XxSmall),
+            "x-small" => Some(AbsoluteSize::XSmall),
+            "small" => Some(AbsoluteSize::Small),
+            "medium" => Some(AbsoluteSize::Medium),
+            "large" => Some(AbsoluteSize::Large),
+            "x-large" => Some(AbsoluteSize::XLarge),
+            "xx-large" => Some(AbsoluteSize::XxLarge),
+            "xxx-large" => Some(AbsoluteSize::XxxLarge),
+            _ => None,
+        }
+    }
+
+    /// mediumに対する倍率
+    fn scale(&self) -> f64 {
+        match self {
+            AbsoluteSize::XxSmall => 3.0 / 5.0,
+            AbsoluteSize::XSmall => 3.0 / 4.0,
+            AbsoluteSize::Small => 8.0 / 9.0,
+            AbsoluteSize::Medium => 1.0,
+            AbsoluteSize::Large => 6.0 / 5.0,
+            AbsoluteSize::XLarge => 3.0 / 2.0,
+            AbsoluteSize::XxLarge => 2.0,
+            AbsoluteSize::XxxLarge => 3.0,
+        }
+    }
+}
+
+impl FontSize {
+    /// "small"、"larger"、"1.2em"、"150%"などをパースする
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        let input = input.trim();
+        let name = input.to_ascii_lowercase();
+        if let Some(size) = AbsoluteSize::from_name(&name) {
+            return Ok(FontSize::Absolute(size));
+        }
+        match name.as_str() {
+            "larger" => Ok(FontSize::Larger),
+            "smaller" => Ok(FontSize::Smaller),
+            _ => {
+                let length = Length::parse(input)?;
+                // font-sizeに負の値は使えない
+                if length.is_negative() {
+                    return Err(Error::UnexpectedInput(format!(
+                        "negative font-size: {}",
+                        input
+                    )));
+                }
+                Ok(FontSize::Length(length))
+            }
+        }
+    }
+
+    /// font-sizeプロパティの計算値をピクセルで求める
+    /// キーワードはmedium(16px)を基準にし、largerとsmallerは親要素のfont-sizeを1.2倍または1.2分の1にする
+    /// 長さはLength::compute_font_sizeと同じように解決する
+    pub fn compute(&self, parent_font_size: f64, context: &LengthContext) -> f64 {
+        match self {
+            FontSize::Absolute(size) => MEDIUM_FONT_SIZE * size.scale(),
+            FontSize::Larger => parent_font_size * RELATIVE_FONT_SIZE_RATIO,
+            FontSize::Smaller => parent_font_size / RELATIVE_FONT_SIZE_RATIO,
+            FontSize::Length(length) => length.compute_font_size(parent_font_size, context),
+        }
+    }
+}
+
+impl fmt::Display for Length {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Length::Px(px) => write!(f, "{}px", px),
+            Length::Em(em) => write!(f, "{}em", em),
+            Length::Rem(rem) => write!(f, "{}rem", rem),
+            Length::Percent(percent) => write!(f, "{}%", percent),
+            Length::Vw(vw) => write!(f, "{}vw", vw),
+            Length::Vh(vh) => write!(f, "{}vh", vh),
+            Length::Vmin(vmin) => write!(f, "{}vmin", vmin),
+            Length::Vmax(vmax) => write!(f, "{}vmax", vmax),
+            Length::Pt(pt) => write!(f, "{}pt", pt),
+            Length::Pc(pc) => write!(f, "{}pc", pc),
+            Length::In(inches) => write!(f, "{}in", inches),
+            Length::Cm(cm) => write!(f, "{}cm", cm),
+            Length::Mm(mm) => write!(f, "{}mm", mm),
+            Length::Q(q) => write!(f, "{}Q", q),
+        }
+    }
+}
+
+/// "1.5em"を"1.5"と"em"に分ける
+/// https://www.w3.org/TR/css-syntax-3/#consume-number
+pub(crate) fn split_number(input: &str) -> (&str, &str) {
+    let bytes = input.as_bytes();
+    let digits_from = |mut i: usize| {
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        i
+    };
+
+    let mut end = 0;
+    if end < bytes.len() && (bytes[end] == b'+' || bytes[end] == b'-') {
+        end += 1;
+    }
+    end = digits_from(end);
+    if end + 1 < bytes.len() && bytes[end] == b'.' && bytes[end + 1].is_ascii_digit() {
+        end = digits_from(end + 1);
+    }
+    // "1em"のeは指数ではなく単位の一部なので、後に数字が続くときだけ指数とみなす
+    if end < bytes.len() && (bytes[end] == b'e' || bytes[end] == b'E') {
+        let mut exponent = end + 1;
+        if exponent < bytes.len() && (bytes[exponent] == b'+' || bytes[exponent] == b'-') {
+            exponent += 1;
+        }
+        if exponent < bytes.len() && bytes[exponent].is_ascii_digit() {
+            end = digits_from(exponent);
+        }
+    }
+    input.split_at(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Length::parse("10px"), Ok(Length::Px(10.0)));
+        assert_eq!(Length::parse("1.5EM"), Ok(Length::Em(1.5)));
+        assert_eq!(Length::parse("-2rem"), Ok(Length::Rem(-2.0)));
+        assert_eq!(Length::parse(".5em"), Ok(Length::Em(0.5)));
+        assert_eq!(Length::parse("50%"), Ok(Length::Percent(50.0)));
+        assert_eq!(Length::parse("1e1px"), Ok(Length::Px(10.0)));
+        assert_eq!(Length::parse("0"), Ok(Length::Px(0.0)));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(Length::parse("").is_err());
+        assert!(Length::parse("px").is_err());
+        assert!(Length::parse("10").is_err());
+        assert!(Length::parse("10 px").is_err());
+        assert!(Length::parse("10qq").is_err());
+        assert!(Length::parse("10vx").is_err());
+        assert!(Length::parse("1.px").is_err());
+    }
+
+    #[test]
+    fn test_to_px() {
+        let context = LengthContext::new(20.0, 16.0);
+        assert_eq!(Length::Px(12.0).to_px(&context, 300.0), 12.0);
+        assert_eq!(Length::Em(1.5).to_px(&context, 300.0), 30.0);
+        assert_eq!(Length::Rem(2.0).to_px(&context, 300.0), 32.0);
+        assert_eq!(Length::Percent(10.0).to_px(&context, 300.0), 30.0);
+    }
+
+    #[test]
+    fn test_parse_units() {
+        assert_eq!(Length::parse("100vh"), Ok(Length::Vh(100.0)));
+        assert_eq!(Length::parse("50VW"), Ok(Length::Vw(50.0)));
+        assert_eq!(Length::parse("10vmin"), Ok(Length::Vmin(10.0)));
+        assert_eq!(Length::parse("10vmax"), Ok(Length::Vmax(10.0)));
+        assert_eq!(Length::parse("12pt"), Ok(Length::Pt(12.0)));
+        assert_eq!(Length::parse("1pc"), Ok(Length::Pc(1.0)));
+        assert_eq!(Length::parse("1in"), Ok(Length::In(1.0)));
+        assert_eq!(Length::parse("2.54cm"), Ok(Length::Cm(2.54)));
+        assert_eq!(Length::parse("10mm"), Ok(Length::Mm(10.0)));
+        assert_eq!(Length::parse("4Q"), Ok(Length::Q(4.0)));
+    }
+
+    #[test]
+    fn test_viewport_units() {
+        let context = LengthContext::new(16.0, 16.0).with_viewport(800.0, 600.0);
+        assert_eq!(Length::Vw(50.0).to_px(&context, 0.0), 400.0);
+        assert_eq!(Length::Vh(100.0).to_px(&context, 0.0), 600.0);
+        assert_eq!(Length::Vmin(10.0).to_px(&context, 0.0), 60.0);
+        assert_eq!(Length::Vmax(10.0).to_px(&context, 0.0), 80.0);
+    }
+
+    #[test]
+    fn test_absolute_units() {
+        let context = LengthContext::new(16.0, 16.0);
+        assert_eq!(context.dpi(), 96.0);
+        assert_eq!(Length::In(1.0).to_px(&context, 0.0), 96.0);
+        assert_eq!(Length::Pt(12.0).to_px(&context, 0.0), 16.0);
+        assert_eq!(Length::Pc(1.0).to_px(&context, 0.0), 16.0);
+        // cmやmmは小数の誤差が出るので、近い値であればよい
+        let close_to_inch = |length: Length| (length.to_px(&context, 0.0) - 96.0).abs() < 1e-9;
+        assert!(close_to_inch(Length::Cm(2.54)));
+        assert!(close_to_inch(Length::Mm(25.4)));
+        assert!(close_to_inch(Length::Q(101.6)));
+
+        let context = context.with_dpi(144.0);
+        assert_eq!(Length::In(1.0).to_px(&context, 0.0), 144.0);
+        assert_eq!(Length::Pt(36.0).to_px(&context, 0.0), 72.0);
+    }
+
+    #[test]
+    fn test_compute_font_size_viewport_and_dpi() {
+        let context = LengthContext::new(16.0, 16.0)
+            .with_viewport(800.0, 600.0)
+            .with_dpi(144.0);
+        assert_eq!(Length::Vw(5.0).compute_font_size(16.0, &context), 40.0);
+        assert_eq!(Length::Vh(5.0).compute_font_size(16.0, &context), 30.0);
+        assert_eq!(Length::Pt(12.0).compute_font_size(16.0, &context), 24.0);
+        assert_eq!(
+            Length::Pt(12.0).compute_font_size(16.0, &LengthContext::new(16.0, 16.0)),
+            16.0
+        );
+    }
+
+    #[test]
+    fn test_compute_font_size() {
+        // 要素自身のfont-sizeは、親要素のfont-sizeで上書きされる
+        let root = LengthContext::new(99.0, 16.0);
+        assert_eq!(Length::Em(1.2).compute_font_size(20.0, &root), 24.0);
+        assert_eq!(Length::Percent(150.0).compute_font_size(20.0, &root), 30.0);
+        assert_eq!(Length::Rem(1.0).compute_font_size(20.0, &root), 16.0);
+
+        // font-size: 1.2emの要素のpadding: 1emは、自身のfont-sizeを基準にする
+        let font_size = Length::Em(1.2).compute_font_size(20.0, &root);
+        let context = LengthContext::new(font_size, 16.0);
+        assert_eq!(Length::Em(1.0).to_px(&context, 500.0), 24.0);
+        assert_eq!(Length::Percent(10.0).to_px(&context, 500.0), 50.0);
+    }
+
+    #[test]
+    fn test_font_size_keywords() {
+        let context = LengthContext::new(20.0, 16.0);
+        let compute = |input: &str| {
+            FontSize::parse(input)
+                .expect("failed to parse font-size")
+                .compute(20.0, &context)
+        };
+        assert_eq!(compute("medium"), 16.0);
+        assert_eq!(compute("XX-SMALL"), 9.6);
+        assert_eq!(compute("x-small"), 12.0);
+        assert_eq!(compute("large"), 19.2);
+        assert_eq!(compute("x-large"), 24.0);
+        assert_eq!(compute("xx-large"), 32.0);
+        assert_eq!(compute("xxx-large"), 48.0);
+        assert_eq!(compute("larger"), 24.0);
+        assert!((compute("smaller") - 20.0 / 1.2).abs() < 1e-9);
+        assert_eq!(compute("1.5em"), 30.0);
+        assert_eq!(compute("50%"), 10.0);
+    }
+
+    #[test]
+    fn test_font_size_invalid() {
+        assert!(FontSize::parse("huge").is_err());
+        assert!(FontSize::parse("-1px").is_err());
+        assert!(FontSize::parse("").is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", Length::Em(1.5)), "1.5em");
+        assert_eq!(format!("{}", Length::Percent(50.0)), "50%");
+    }
+}
@@ -0,0 +1,401 @@
+use crate::css::length::Length;
+use crate::css::length::LengthContext;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+/// メディアクエリの中のemやremの基準 (font-sizeの初期値)
+const INITIAL_FONT_SIZE: f64 = 16.0;
+
+/// https://www.w3.org/TR/mediaqueries-4/#media-types
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MediaType {
+    All,
+    Screen,
+    Print,
+    /// 知らないメディアタイプにはマッチしない
+    Unknown(String),
+}
+
+/// https://www.w3.org/TR/mediaqueries-4/#mq-features
+#[derive(Debug, Clone, PartialEq)]
+pub enum MediaFeature {
+    Width(Length),
+    MinWidth(Length),
+    MaxWidth(Length),
+    Height(Length),
+    MinHeight(Length),
+    MaxHeight(Length),
+}
+
+/// "not screen and (min-width: 600px)"のような1つのクエリ
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaQuery {
+    negated: bool,
+    media_type: MediaType,
+    features: Vec<MediaFeature>,
+}
+
+/// @mediaの条件。カンマで区切られたクエリのどれかにマッチすればよい
+/// https://www.w3.org/TR/mediaqueries-4/#mq-list
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaQueryList {
+    queries: Vec<MediaQuery>,
+}
+
+/// メディアクエリを評価するためのビューポートなどの情報
+/// ウィンドウの大きさが変わったら、監視しているクエリを評価し直す
+#[derive(Debug, Clone)]
+pub struct MediaContext {
+    media_type: MediaType,
+    width: f64,
+    height: f64,
+    watched: Vec<(MediaQueryList, bool)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    /// "(min-width: 600px)"の括弧の中身
+    Feature(String),
+}
+
+impl MediaType {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "all" => MediaType::All,
+            "screen" => MediaType::Screen,
+            "print" => MediaType::Print,
+            _ => MediaType::Unknown(name.to_string()),
+        }
+    }
+}
+
+impl MediaFeature {
+    /// "min-width: 600px"をパースする
+    fn parse(input: &str) -> Option<Self> {
+        let (name, value) = input.split_once(':')?;
+        let value = match Length::parse(value).ok()? {
+            // %はビューポートに対して定義されていない
+            Length::Percent(_) => return None,
+            value => value,
+        };
+        match name.trim().to_ascii_lowercase().as_str() {
+            "width" => Some(MediaFeature::Width(value)),
+            "min-width" => Some(MediaFeature::MinWidth(value)),
+            "max-width" => Some(MediaFeature::MaxWidth(value)),
+            "height" => Some(MediaFeature::Height(value)),
+            "min-height" => Some(MediaFeature::MinHeight(value)),
+            "max-height" => Some(MediaFeature::MaxHeight(value)),
+            _ => None,
+        }
+    }
+
+    fn evaluate(&self, context: &MediaContext) -> bool {
+        let length_context = LengthContext::new(INITIAL_FONT_SIZE, INITIAL_FONT_SIZE)
+            .with_viewport(context.width, context.height);
+        let px = |length: &Length| length.to_px(&length_context, 0.0);
+        match self {
+            MediaFeature::Width(width) => context.width == px(width),
+            MediaFeature::MinWidth(width) => context.width >= px(width),
+            MediaFeature::MaxWidth(width) => context.width <= px(width),
+            MediaFeature::Height(height) => context.height == px(height),
+            MediaFeature::MinHeight(height) => context.height >= px(height),
+            MediaFeature::MaxHeight(height) => context.height <= px(height),
+        }
+    }
+}
+
+impl MediaQuery {
+    /// 構文として正しくないクエリは"not all"として扱う
+    /// https://www.w3.org/TR/mediaqueries-4/#error-handling
+    fn not_all() -> Self {
+        Self {
+            negated: true,
+            media_type: MediaType::All,
+            features: Vec::new(),
+        }
+    }
+
+    fn parse(input: &str) -> Self {
+        match tokenize(input).and_then(|tokens| Self::parse_tokens(&tokens)) {
+            Some(query) => query,
+            None => Self::not_all(),
+        }
+    }
+
+    fn parse_tokens(tokens: &[Token]) -> Option<Self> {
+        let mut tokens = tokens.iter().peekable();
+        let mut negated = false;
+        let mut media_type = MediaType::All;
+        let mut features = Vec::new();
+
+        match tokens.next()? {
+            Token::Ident(ident) => {
+                let name = match ident.as_str() {
+                    "not" | "only" => {
+                        negated = ident == "not";
+                        match tokens.next()? {
+                            Token::Ident(name) => name,
+                            // "not (min-width: 600px)"は条件を反転する。"not"が掛かるのは
+                            // 括弧1つだけなので、後ろに"and"は続けられない
+                            // https://www.w3.org/TR/mediaqueries-4/#typedef-media-not
+                            Token::Feature(feature) if negated && tokens.peek().is_none() => {
+                                features.push(MediaFeature::parse(feature)?);
+                                return Some(Self {
+                                    negated,
+                                    media_type,
+                                    features,
+                                });
+                            }
+                            Token::Feature(_) => return None,
+                        }
+                    }
+                    name => name,
+                };
+                if matches!(name, "not" | "only" | "and") {
+                    return None;
+                }
+                media_type = MediaType::from_name(name);
+            }
+            Token::Feature(feature) => features.push(MediaFeature::parse(feature)?),
+        }
+
+        while let Some(token) = tokens.next() {
+            match (token, tokens.next()) {
+                (Token::Ident(and), Some(Token::Feature(feature))) if and == "and" => {
+                    features.push(MediaFeature::parse(feature)?)
+                }
+                _ => return None,
+            }
+        }
+
+        Some(Self {
+            negated,
+            media_type,
+            features,
+        })
+    }
+
+    pub fn is_negated(&self) -> bool {
+        self.negated
+    }
+    pub fn media_type(&self) -> MediaType {
+        self.media_type.clone()
+    }
+    pub fn features(&self) -> Vec<MediaFeature> {
+        self.features.clone()
+    }
+
+    pub fn evaluate(&self, context: &MediaContext) -> bool {
+        let type_matches = match &self.media_type {
+            MediaType::All => true,
+            MediaType::Unknown(_) => false,
+            media_type => *media_type == context.media_type,
+        };
+        let matches = type_matches && self.features.iter().all(|f| f.evaluate(context));
+        matches != self.negated
+    }
+}
+
+impl MediaQueryList {
+    /// "screen and (max-width: 600px), print"のような@mediaの条件をパースする
+    /// 正しくないクエリがあってもリスト全体は無効にならず、そのクエリだけがマッチしなくなる
+    pub fn parse(input: &str) -> Self {
+        let input = input.trim();
+        // 空のリストは常にマッチする
+        if input.is_empty() {
+            return Self {
+                queries: Vec::new(),
+            };
+        }
+        Self {
+            queries: input.split(',').map(MediaQuery::parse).collect(),
+        }
+    }
+
+    pub fn queries(&self) -> Vec<MediaQuery> {
+        self.queries.clone()
+    }
+
+    pub fn evaluate(&self, context: &MediaContext) -> bool {
+        self.queries.is_empty() || self.queries.iter().any(|q| q.evaluate(context))
+    }
+}
+
+impl MediaContext {
+    pub fn new(media_type: MediaType, width: f64, height: f64) -> Self {
+        Self {
+            media_type,
+            width,
+            height,
+            watched: Vec::new(),
+        }
+    }
+
+    pub fn media_type(&self) -> MediaType {
+        self.media_type.clone()
+    }
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+    pub fn height(&self) -> f64 {
+        self.height
+    }
+
+    /// ビューポートの大きさが変わったときに評価し直すクエリを登録し、そのIDを返す
+    pub fn watch(&mut self, query: MediaQueryList) -> usize {
+        let matches = query.evaluate(self);
+        self.watched.push((query, matches));
+        self.watched.len() - 1
+    }
+
+    /// watchで登録したクエリの最新の評価結果
+    pub fn matches(&self, id: usize) -> bool {
+        self.watched.get(id).is_some_and(|(_, matches)| *matches)
+    }
+
+    /// ビューポートの大きさを更新し、評価結果が変わったクエリのIDを返す
+    pub fn resize(&mut self, width: f64, height: f64) -> Vec<usize> {
+        self.width = width;
+        self.height = height;
+
+        let mut changed = Vec::new();
+        for id in 0..self.watched.len() {
+            let matches = self.watched[id].0.evaluate(self);
+            if matches != self.watched[id].1 {
+                self.watched[id].1 = matches;
+                changed.push(id);
+            }
+        }
+        changed
+    }
+}
+
+/// 識別子は小文字にそろえる。括弧が閉じていなければNone
+fn tokenize(input: &str) -> Option<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' => {
+                let mut feature = String::new();
+                loop {
+                    match chars.next()? {
+                        ')' => break,
+                        c => feature.push(c),
+                    }
+                }
+                tokens.push(Token::Feature(feature));
+            }
+            c if c.is_alphanumeric() || c == '-' => {
+                let mut ident = String::from(c);
+                while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '-') {
+                    ident.push(c);
+                }
+                tokens.push(Token::Ident(ident.to_ascii_lowercase()));
+            }
+            _ => return None,
+        }
+    }
+    Some(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn screen(width: f64) -> MediaContext {
+        MediaContext::new(MediaType::Screen, width, 600.0)
+    }
+
+    #[test]
+    fn test_parse() {
+        let list = MediaQueryList::parse("not screen and (min-width:600px) AND (max-width: 50em)");
+        assert_eq!(
+            list.queries(),
+            vec![MediaQuery {
+                negated: true,
+                media_type: MediaType::Screen,
+                features: vec![
+                    MediaFeature::MinWidth(Length::Px(600.0)),
+                    MediaFeature::MaxWidth(Length::Em(50.0)),
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_media_type() {
+        assert!(MediaQueryList::parse("screen").evaluate(&screen(800.0)));
+        assert!(MediaQueryList::parse("all").evaluate(&screen(800.0)));
+        assert!(!MediaQueryList::parse("print").evaluate(&screen(800.0)));
+        assert!(MediaQueryList::parse("not print").evaluate(&screen(800.0)));
+        assert!(MediaQueryList::parse("only screen").evaluate(&screen(800.0)));
+        assert!(!MediaQueryList::parse("tv").evaluate(&screen(800.0)));
+        assert!(MediaQueryList::parse("print, screen").evaluate(&screen(800.0)));
+        assert!(MediaQueryList::parse("").evaluate(&screen(800.0)));
+    }
+
+    #[test]
+    fn test_width() {
+        let list = MediaQueryList::parse("(min-width: 600px) and (max-width: 60em)");
+        assert!(!list.evaluate(&screen(599.0)));
+        assert!(list.evaluate(&screen(600.0)));
+        assert!(list.evaluate(&screen(960.0)));
+        assert!(!list.evaluate(&screen(961.0)));
+        assert!(MediaQueryList::parse("(width: 800px)").evaluate(&screen(800.0)));
+        assert!(MediaQueryList::parse("(max-height: 600px)").evaluate(&screen(800.0)));
+        assert!(!MediaQueryList::parse("(min-height: 601px)").evaluate(&screen(800.0)));
+        assert!(MediaQueryList::parse("(min-width: 100vh)").evaluate(&screen(800.0)));
+        assert!(!MediaQueryList::parse("(min-width: 100vh)").evaluate(&screen(599.0)));
+    }
+
+    #[test]
+    fn test_not_feature() {
+        let list = MediaQueryList::parse("not (min-width: 100px)");
+        assert!(list.queries()[0].is_negated());
+        assert_eq!(list.queries()[0].media_type(), MediaType::All);
+        assert!(list.evaluate(&screen(50.0)));
+        assert!(!list.evaluate(&screen(100.0)));
+
+        assert!(
+            !MediaQueryList::parse("not (min-width: 100px) and (max-width: 10px)")
+                .evaluate(&screen(50.0))
+        );
+        assert!(!MediaQueryList::parse("only (min-width: 10px)").evaluate(&screen(50.0)));
+        assert!(!MediaQueryList::parse("not (color-gamut: p3)").evaluate(&screen(50.0)));
+    }
+
+    #[test]
+    fn test_invalid_query() {
+        // 正しくないクエリだけがnot allになる
+        let list = MediaQueryList::parse("screen and, print");
+        assert_eq!(list.queries()[0], MediaQuery::not_all());
+        assert!(!list.evaluate(&screen(800.0)));
+
+        let list = MediaQueryList::parse("(min-width: 50%), screen");
+        assert!(list.evaluate(&screen(800.0)));
+        assert!(!MediaQueryList::parse("(color-gamut: p3)").evaluate(&screen(800.0)));
+        assert!(!MediaQueryList::parse("(min-width: 600px").evaluate(&screen(800.0)));
+        assert!(!MediaQueryList::parse("screen (min-width: 1px)").evaluate(&screen(800.0)));
+        assert!(!MediaQueryList::parse("not").evaluate(&screen(800.0)));
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut context = screen(1024.0);
+        let narrow = context.watch(MediaQueryList::parse("(max-width: 600px)"));
+        let wide = context.watch(MediaQueryList::parse("(min-width: 800px)"));
+        assert!(!context.matches(narrow));
+        assert!(context.matches(wide));
+
+        assert_eq!(context.resize(900.0, 600.0), Vec::<usize>::new());
+        assert_eq!(context.resize(500.0, 600.0), vec![narrow, wide]);
+        assert!(context.matches(narrow));
+        assert!(!context.matches(wide));
+        assert_eq!(context.width(), 500.0);
+    }
+}
@@ -0,0 +1,887 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::Peekable;
+use core::str::Chars;
+
+/// https://www.w3.org/TR/selectors-4/#simple
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SimpleSelector {
+    /// *
+    Universal,
+    /// div
+    Type(String),
+    /// .warn
+    Class(String),
+    /// #main
+    Id(String),
+    /// :hover
+    PseudoClass(PseudoClass),
+}
+
+/// https://www.w3.org/TR/selectors-4/#pseudo-classes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PseudoClass {
+    Hover,
+    Active,
+    Focus,
+    FirstChild,
+    LastChild,
+    NthChild(Nth),
+    NthLastChild(Nth),
+}
+
+/// :nth-child()の引数のAn+B
+/// https://www.w3.org/TR/css-syntax-3/#anb-microsyntax
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Nth {
+    a: i32,
+    b: i32,
+}
+
+/// ユーザーの操作によって変わる要素の状態
+/// ブラウザの入力処理がDOMの要素に設定し、:hoverなどの動的擬似クラスのマッチングに使う
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ElementState {
+    bits: u8,
+}
+
+/// セレクタとのマッチングに必要な要素の情報
+/// DOMの実装ごとに実装する
+pub trait SelectorElement {
+    /// 小文字の要素名
+    fn local_name(&self) -> String;
+    fn id(&self) -> Option<String>;
+    fn has_class(&self, name: &str) -> bool;
+    fn state(&self) -> ElementState;
+    /// 親要素の子要素の中での位置 (1始まりの番号, 兄弟要素を含めた要素数)
+    /// テキストなど要素以外のノードは数えない。親がない場合は(1, 1)
+    fn position_in_parent(&self) -> (usize, usize);
+}
+
+/// div.warn#main のように、空白を挟まずに並べた単純セレクタの集まり
+/// https://www.w3.org/TR/selectors-4/#compound
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompoundSelector {
+    components: Vec<SimpleSelector>,
+}
+
+/// "h1, .warn" のように、カンマで区切られたセレクタの並び
+/// https://www.w3.org/TR/selectors-4/#selector-list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorList {
+    selectors: Vec<CompoundSelector>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelectorParseError {
+    /// セレクタが空だった ("" や "a, ,b" など)
+    Empty,
+    /// . や # の後に名前がなかった
+    MissingName(char),
+    /// 型セレクタや*が複合セレクタの先頭以外に現れた
+    MisplacedTypeSelector(String),
+    /// 子孫結合子などの結合子はまだサポートしていない
+    UnsupportedCombinator(char),
+    UnknownPseudoClass(String),
+    /// :nth-child()などの引数が不正だった
+    InvalidArgument(String),
+    UnexpectedCharacter(char),
+}
+
+impl fmt::Display for SelectorParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectorParseError::Empty => write!(f, "Empty selector."),
+            SelectorParseError::MissingName(c) => write!(f, "Missing name after {:?}.", c),
+            SelectorParseError::MisplacedTypeSelector(name) => {
+                write!(f, "Type selector must come first: {}", name)
+            }
+            SelectorParseError::UnsupportedCombinator(c) => {
+                write!(f, "Unsupported combinator: {:?}", c)
+            }
+            SelectorParseError::UnknownPseudoClass(name) => {
+                write!(f, "Unknown pseudo-class: :{}", name)
+            }
+            SelectorParseError::InvalidArgument(argument) => {
+                write!(f, "Invalid argument to pseudo-class: {}", argument)
+            }
+            SelectorParseError::UnexpectedCharacter(c) => {
+                write!(f, "Unexpected character in selector: {:?}", c)
+            }
+        }
+    }
+}
+
+impl ElementState {
+    pub const HOVER: Self = Self { bits: 1 };
+    pub const ACTIVE: Self = Self { bits: 1 << 1 };
+    pub const FOCUS: Self = Self { bits: 1 << 2 };
+
+    pub fn empty() -> Self {
+        Self { bits: 0 }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    pub fn contains(&self, other: Self) -> bool {
+        self.bits & other.bits == other.bits
+    }
+
+    pub fn intersects(&self, other: Self) -> bool {
+        self.bits & other.bits != 0
+    }
+
+    pub fn insert(&mut self, other: Self) {
+        self.bits |= other.bits;
+    }
+
+    pub fn remove(&mut self, other: Self) {
+        self.bits &= !other.bits;
+    }
+
+    pub fn set(&mut self, other: Self, value: bool) {
+        if value {
+            self.insert(other);
+        } else {
+            self.remove(other);
+        }
+    }
+
+    /// 変更前と変更後の状態から、変わったフラグだけを取り出す
+    pub fn changed(old: Self, new: Self) -> Self {
+        Self {
+            bits: old.bits ^ new.bits,
+        }
+    }
+}
+
+impl PseudoClass {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "hover" => Some(PseudoClass::Hover),
+            "active" => Some(PseudoClass::Active),
+            "focus" => Some(PseudoClass::Focus),
+            "first-child" => Some(PseudoClass::FirstChild),
+            "last-child" => Some(PseudoClass::LastChild),
+            _ => None,
+        }
+    }
+
+    /// :nth-child(2n+1)のような、引数を取る擬似クラス
+    fn from_function(name: &str, argument: &str) -> Result<Self, SelectorParseError> {
+        let constructor = match name.to_ascii_lowercase().as_str() {
+            "nth-child" => PseudoClass::NthChild,
+            "nth-last-child" => PseudoClass::NthLastChild,
+            _ => {
+                return Err(SelectorParseError::UnknownPseudoClass(format!(
+                    "{}()",
+                    name
+                )));
+            }
+        };
+        match Nth::parse(argument) {
+            Some(nth) => Ok(constructor(nth)),
+            None => Err(SelectorParseError::InvalidArgument(format!(
+                "{}({})",
+                name, argument
+            ))),
+        }
+    }
+
+    /// 動的擬似クラスが参照する要素の状態
+    pub fn state(&self) -> Option<ElementState> {
+        match self {
+            PseudoClass::Hover => Some(ElementState::HOVER),
+            PseudoClass::Active => Some(ElementState::ACTIVE),
+            PseudoClass::Focus => Some(ElementState::FOCUS),
+            _ => None,
+        }
+    }
+
+    pub fn matches<E: SelectorElement>(&self, element: &E) -> bool {
+        if let Some(state) = self.state() {
+            return element.state().contains(state);
+        }
+
+        let (index, count) = element.position_in_parent();
+        match self {
+            PseudoClass::FirstChild => index == 1,
+            PseudoClass::LastChild => index == count,
+            PseudoClass::NthChild(nth) => nth.matches(index),
+            // 後ろから数える
+            PseudoClass::NthLastChild(nth) => nth.matches(count + 1 - index),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for PseudoClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PseudoClass::Hover => write!(f, "hover"),
+            PseudoClass::Active => write!(f, "active"),
+            PseudoClass::Focus => write!(f, "focus"),
+            PseudoClass::FirstChild => write!(f, "first-child"),
+            PseudoClass::LastChild => write!(f, "last-child"),
+            PseudoClass::NthChild(nth) => write!(f, "nth-child({})", nth),
+            PseudoClass::NthLastChild(nth) => write!(f, "nth-last-child({})", nth),
+        }
+    }
+}
+
+impl Nth {
+    pub fn new(a: i32, b: i32) -> Self {
+        Self { a, b }
+    }
+
+    /// "odd"、"even"、"3"、"-n+3"、"2n + 1"などを受け付ける
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim_matches(is_whitespace).to_ascii_lowercase();
+        match input.as_str() {
+            "odd" => return Some(Self::new(2, 1)),
+            "even" => return Some(Self::new(2, 0)),
+            _ => {}
+        }
+
+        let (a, rest) = match input.split_once('n') {
+            Some((a, rest)) => (a, rest),
+            None => return Some(Self::new(0, parse_integer(&input, true)?)),
+        };
+        let a = match a {
+            "" | "+" => 1,
+            "-" => -1,
+            _ => parse_integer(a, true)?,
+        };
+
+        // "n"の後は、空白を挟んで"+ 1"や"-1"が続くことがある
+        let rest = rest.trim_start_matches(is_whitespace);
+        let b = if rest.is_empty() {
+            0
+        } else if let Some(b) = rest.strip_prefix('+') {
+            parse_integer(b.trim_start_matches(is_whitespace), false)?
+        } else if let Some(b) = rest.strip_prefix('-') {
+            -parse_integer(b.trim_start_matches(is_whitespace), false)?
+        } else {
+            return None;
+        };
+        Some(Self::new(a, b))
+    }
+
+    pub fn a(&self) -> i32 {
+        self.a
+    }
+    pub fn b(&self) -> i32 {
+        self.b
+    }
+
+    /// 1始まりのindexが、0以上のnについてan+bと等しくなるか
+    pub fn matches(&self, index: usize) -> bool {
+        let index = index as i64;
+        let (a, b) = (self.a as i64, self.b as i64);
+        if a == 0 {
+            return index == b;
+        }
+        let diff = index - b;
+        diff % a == 0 && diff / a >= 0
+    }
+}
+
+impl fmt::Display for Nth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.a {
+            0 => return write!(f, "{}", self.b),
+            1 => write!(f, "n")?,
+            -1 => write!(f, "-n")?,
+            a => write!(f, "{}n", a)?,
+        }
+        match self.b {
+            0 => Ok(()),
+            b if b > 0 => write!(f, "+{}", b),
+            b => write!(f, "{}", b),
+        }
+    }
+}
+
+impl SimpleSelector {
+    /// https://www.w3.org/TR/selectors-4/#specificity-rules
+    /// (IDセレクタの数, クラスセレクタの数, 型セレクタの数)
+    pub fn specificity(&self) -> (u32, u32, u32) {
+        match self {
+            SimpleSelector::Universal => (0, 0, 0),
+            SimpleSelector::Type(_) => (0, 0, 1),
+            SimpleSelector::Class(_) | SimpleSelector::PseudoClass(_) => (0, 1, 0),
+            SimpleSelector::Id(_) => (1, 0, 0),
+        }
+    }
+
+    pub fn matches<E: SelectorElement>(&self, element: &E) -> bool {
+        match self {
+            SimpleSelector::Universal => true,
+            SimpleSelector::Type(name) => element.local_name() == *name,
+            SimpleSelector::Class(name) => element.has_class(name),
+            SimpleSelector::Id(id) => element.id().as_ref() == Some(id),
+            SimpleSelector::PseudoClass(pseudo_class) => pseudo_class.matches(element),
+        }
+    }
+}
+
+impl fmt::Display for SimpleSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimpleSelector::Universal => write!(f, "*"),
+            SimpleSelector::Type(name) => write!(f, "{}", name),
+            SimpleSelector::Class(name) => write!(f, ".{}", name),
+            SimpleSelector::Id(name) => write!(f, "#{}", name),
+            SimpleSelector::PseudoClass(pseudo_class) => write!(f, ":{}", pseudo_class),
+        }
+    }
+}
+
+impl CompoundSelector {
+    pub fn parse(input: &str) -> Result<Self, SelectorParseError> {
+        let input = input.trim_matches(is_whitespace);
+        if input.is_empty() {
+            return Err(SelectorParseError::Empty);
+        }
+
+        let mut chars = input.chars().peekable();
+        let mut components = Vec::new();
+        while let Some(c) = chars.next() {
+            let component = match c {
+                '*' => SimpleSelector::Universal,
+                '.' => SimpleSelector::Class(consume_name(&mut chars, c)?),
+                '#' => SimpleSelector::Id(consume_name(&mut chars, c)?),
+                ':' => {
+                    let name = consume_name(&mut chars, c)?;
+                    if chars.next_if_eq(&'(').is_some() {
+                        let mut argument = String::new();
+                        loop {
+                            match chars.next() {
+                                Some(')') => break,
+                                Some(c) => argument.push(c),
+                                None => {
+                                    return Err(SelectorParseError::InvalidArgument(format!(
+                                        "{}({}",
+                                        name, argument
+                                    )));
+                                }
+                            }
+                        }
+                        SimpleSelector::PseudoClass(PseudoClass::from_function(&name, &argument)?)
+                    } else {
+                        match PseudoClass::from_name(&name) {
+                            Some(pseudo_class) => SimpleSelector::PseudoClass(pseudo_class),
+                            None => return Err(SelectorParseError::UnknownPseudoClass(name)),
+                        }
+                    }
+                }
+                c if is_name_start(c) || c == '\\' => {
+                    let mut name = String::new();
+                    push_name_char(&mut name, &mut chars, c);
+                    name.push_str(&consume_name_rest(&mut chars));
+                    // HTMLの要素名は大文字小文字を区別しない
+                    SimpleSelector::Type(name.to_ascii_lowercase())
+                }
+                c if is_whitespace(c) || c == '>' || c == '+' || c == '~' => {
+                    // "div > p"では空白ではなく、結合子そのものを報告する
+                    while chars.peek().copied().is_some_and(is_whitespace) {
+                        chars.next();
+                    }
+                    let combinator = match chars.peek() {
+                        Some(&next) if is_whitespace(c) && matches!(next, '>' | '+' | '~') => next,
+                        _ => c,
+                    };
+                    return Err(SelectorParseError::UnsupportedCombinator(combinator));
+                }
+                _ => return Err(SelectorParseError::UnexpectedCharacter(c)),
+            };
+
+            if matches!(
+                component,
+                SimpleSelector::Universal | SimpleSelector::Type(_)
+            ) && !components.is_empty()
+            {
+                return Err(SelectorParseError::MisplacedTypeSelector(
+                    component.to_string(),
+                ));
+            }
+            components.push(component);
+        }
+
+        Ok(Self { components })
+    }
+
+    pub fn components(&self) -> Vec<SimpleSelector> {
+        self.components.clone()
+    }
+
+    pub fn specificity(&self) -> (u32, u32, u32) {
+        self.components
+            .iter()
+            .map(|c| c.specificity())
+            .fold((0, 0, 0), |(a, b, c), (x, y, z)| (a + x, b + y, c + z))
+    }
+
+    pub fn matches<E: SelectorElement>(&self, element: &E) -> bool {
+        self.components.iter().all(|c| c.matches(element))
+    }
+
+    /// 要素の状態がchangedの分だけ変わったとき、マッチングの結果が変わりうるか
+    fn depends_on_state(&self, changed: ElementState) -> bool {
+        self.components.iter().any(|c| match c {
+            SimpleSelector::PseudoClass(pseudo_class) => pseudo_class
+                .state()
+                .is_some_and(|state| changed.intersects(state)),
+            _ => false,
+        })
+    }
+}
+
+impl fmt::Display for CompoundSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for component in &self.components {
+            write!(f, "{}", component)?;
+        }
+        Ok(())
+    }
+}
+
+impl SelectorList {
+    pub fn parse(input: &str) -> Result<Self, SelectorParseError> {
+        let mut selectors = Vec::new();
+        for selector in input.split(',') {
+            selectors.push(CompoundSelector::parse(selector)?);
+        }
+        Ok(Self { selectors })
+    }
+
+    pub fn selectors(&self) -> Vec<CompoundSelector> {
+        self.selectors.clone()
+    }
+
+    pub fn matches<E: SelectorElement>(&self, element: &E) -> bool {
+        self.selectors.iter().any(|s| s.matches(element))
+    }
+
+    /// 要素の状態が変わったとき、スタイルを計算し直す必要があるか
+    /// ElementState::changed(old, new)を渡す
+    pub fn depends_on_state(&self, changed: ElementState) -> bool {
+        self.selectors.iter().any(|s| s.depends_on_state(changed))
+    }
+}
+
+impl fmt::Display for SelectorList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, selector) in self.selectors.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", selector)?;
+        }
+        Ok(())
+    }
+}
+
+/// 符号付きの10進整数を読む。signedがfalseなら符号は許さない
+fn parse_integer(input: &str, signed: bool) -> Option<i32> {
+    let digits = if signed {
+        input.strip_prefix(['+', '-']).unwrap_or(input)
+    } else {
+        input
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    input.parse().ok()
+}
+
+/// https://www.w3.org/TR/css-syntax-3/#whitespace
+fn is_whitespace(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\r' | '\x0C')
+}
+
+/// https://www.w3.org/TR/css-syntax-3/#ident-start-code-point
+fn is_name_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || !c.is_ascii()
+}
+
+/// https://www.w3.org/TR/css-syntax-3/#ident-code-point
+fn is_name_char(c: char) -> bool {
+    is_name_start(c) || c.is_ascii_digit() || c == '-'
+}
+
+/// https://www.w3.org/TR/css-syntax-3/#consume-escaped-code-point
+fn push_name_char(name: &mut String, chars: &mut Peekable<Chars>, c: char) {
+    if c != '\\' {
+        name.push(c);
+        return;
+    }
+
+    let mut hex = String::new();
+    while hex.len() < 6
+        && let Some(&d) = chars.peek()
+        && d.is_ascii_hexdigit()
+    {
+        hex.push(d);
+        chars.next();
+    }
+    if hex.is_empty() {
+        if let Some(escaped) = chars.next() {
+            name.push(escaped);
+        }
+        return;
+    }
+    // 16進数のエスケープの後の空白1つはエスケープの一部
+    if chars.peek().copied().is_some_and(is_whitespace) {
+        chars.next();
+    }
+    let code_point = u32::from_str_radix(&hex, 16).unwrap_or(0);
+    match char::from_u32(code_point) {
+        Some(c) if code_point != 0 => name.push(c),
+        _ => name.push('\u{FFFD}'),
+    }
+}
+
+/// https://www.w3.org/TR/css-syntax-3/#would-start-an-identifier
+fn starts_name(chars: &Peekable<Chars>) -> bool {
+    let mut lookahead = chars.clone();
+    match lookahead.next() {
+        Some('-') => {
+            matches!(lookahead.next(), Some(c) if is_name_start(c) || c == '-' || c == '\\')
+        }
+        Some(c) => is_name_start(c) || c == '\\',
+        None => false,
+    }
+}
+
+fn consume_name_rest(chars: &mut Peekable<Chars>) -> String {
+    let mut name = String::new();
+    while let Some(&c) = chars.peek() {
+        if !is_name_char(c) && c != '\\' {
+            break;
+        }
+        chars.next();
+        push_name_char(&mut name, chars, c);
+    }
+    name
+}
+
+/// . や # の後に続く名前を読む
+fn consume_name(chars: &mut Peekable<Chars>, prefix: char) -> Result<String, SelectorParseError> {
+    if !starts_name(chars) {
+        return Err(SelectorParseError::MissingName(prefix));
+    }
+    Ok(consume_name_rest(chars))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    struct TestElement {
+        local_name: String,
+        id: Option<String>,
+        classes: Vec<String>,
+        state: ElementState,
+        position: (usize, usize),
+    }
+
+    impl SelectorElement for TestElement {
+        fn local_name(&self) -> String {
+            self.local_name.clone()
+        }
+        fn id(&self) -> Option<String> {
+            self.id.clone()
+        }
+        fn has_class(&self, name: &str) -> bool {
+            self.classes.iter().any(|c| c == name)
+        }
+        fn state(&self) -> ElementState {
+            self.state
+        }
+        fn position_in_parent(&self) -> (usize, usize) {
+            self.position
+        }
+    }
+
+    fn element(local_name: &str, id: Option<&str>, classes: &[&str]) -> TestElement {
+        TestElement {
+            local_name: local_name.to_string(),
+            id: id.map(|id| id.to_string()),
+            classes: classes.iter().map(|c| c.to_string()).collect(),
+            state: ElementState::empty(),
+            position: (1, 1),
+        }
+    }
+
+    #[test]
+    fn test_type_selector() {
+        let selector = CompoundSelector::parse("DIV").expect("failed to parse selector");
+        assert_eq!(
+            selector.components(),
+            vec![SimpleSelector::Type("div".to_string())]
+        );
+        assert_eq!(selector.specificity(), (0, 0, 1));
+    }
+
+    #[test]
+    fn test_compound_selector() {
+        let selector =
+            CompoundSelector::parse("p.warn#main.big").expect("failed to parse selector");
+        assert_eq!(
+            selector.components(),
+            vec![
+                SimpleSelector::Type("p".to_string()),
+                SimpleSelector::Class("warn".to_string()),
+                SimpleSelector::Id("main".to_string()),
+                SimpleSelector::Class("big".to_string()),
+            ]
+        );
+        assert_eq!(selector.specificity(), (1, 2, 1));
+        assert_eq!(selector.to_string(), "p.warn#main.big");
+    }
+
+    #[test]
+    fn test_universal_selector() {
+        let selector = CompoundSelector::parse("*.a").expect("failed to parse selector");
+        assert_eq!(
+            selector.components(),
+            vec![
+                SimpleSelector::Universal,
+                SimpleSelector::Class("a".to_string())
+            ]
+        );
+        assert_eq!(selector.specificity(), (0, 1, 0));
+    }
+
+    #[test]
+    fn test_selector_list() {
+        let list = SelectorList::parse(" h1 ,.warn,\n#main ").expect("failed to parse selector");
+        assert_eq!(list.selectors().len(), 3);
+        assert_eq!(list.to_string(), "h1, .warn, #main");
+    }
+
+    #[test]
+    fn test_names() {
+        let selector = CompoundSelector::parse(".-foo_bar.\\31 x#ハロー.a\\.b")
+            .expect("failed to parse selector");
+        assert_eq!(
+            selector.components(),
+            vec![
+                SimpleSelector::Class("-foo_bar".to_string()),
+                SimpleSelector::Class("1x".to_string()),
+                SimpleSelector::Id("ハロー".to_string()),
+                SimpleSelector::Class("a.b".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(SelectorList::parse(""), Err(SelectorParseError::Empty));
+        assert_eq!(SelectorList::parse("a, ,b"), Err(SelectorParseError::Empty));
+        assert_eq!(
+            SelectorList::parse(".1a"),
+            Err(SelectorParseError::MissingName('.'))
+        );
+        assert_eq!(
+            SelectorList::parse("#"),
+            Err(SelectorParseError::MissingName('#'))
+        );
+        assert_eq!(
+            SelectorList::parse(".-1"),
+            Err(SelectorParseError::MissingName('.'))
+        );
+        assert_eq!(
+            SelectorList::parse(".a div"),
+            Err(SelectorParseError::UnsupportedCombinator(' '))
+        );
+        assert_eq!(
+            SelectorList::parse("ul > li"),
+            Err(SelectorParseError::UnsupportedCombinator('>'))
+        );
+        assert_eq!(
+            SelectorList::parse(".a*"),
+            Err(SelectorParseError::MisplacedTypeSelector("*".to_string()))
+        );
+        assert_eq!(
+            SelectorList::parse("a[href]"),
+            Err(SelectorParseError::UnexpectedCharacter('['))
+        );
+    }
+
+    #[test]
+    fn test_matches() {
+        let list = SelectorList::parse("p.warn, #main").expect("failed to parse selector");
+        assert!(list.matches(&element("p", None, &["big", "warn"])));
+        assert!(list.matches(&element("div", Some("main"), &[])));
+        assert!(!list.matches(&element("p", None, &["big"])));
+        assert!(!list.matches(&element("div", Some("mainx"), &["warn"])));
+    }
+
+    #[test]
+    fn test_pseudo_class() {
+        let selector = CompoundSelector::parse("a:HOVER:focus").expect("failed to parse selector");
+        assert_eq!(
+            selector.components(),
+            vec![
+                SimpleSelector::Type("a".to_string()),
+                SimpleSelector::PseudoClass(PseudoClass::Hover),
+                SimpleSelector::PseudoClass(PseudoClass::Focus),
+            ]
+        );
+        assert_eq!(selector.specificity(), (0, 2, 1));
+        assert_eq!(selector.to_string(), "a:hover:focus");
+        assert_eq!(
+            SelectorList::parse("a:visited"),
+            Err(SelectorParseError::UnknownPseudoClass(
+                "visited".to_string()
+            ))
+        );
+        assert_eq!(
+            SelectorList::parse("a:"),
+            Err(SelectorParseError::MissingName(':'))
+        );
+    }
+
+    #[test]
+    fn test_element_state() {
+        let mut state = ElementState::empty();
+        assert!(state.is_empty());
+        state.insert(ElementState::HOVER);
+        state.set(ElementState::FOCUS, true);
+        assert!(state.contains(ElementState::HOVER));
+        assert!(state.contains(ElementState::FOCUS));
+        assert!(!state.contains(ElementState::ACTIVE));
+        state.remove(ElementState::HOVER);
+        assert!(!state.intersects(ElementState::HOVER));
+        assert_eq!(
+            ElementState::changed(ElementState::FOCUS, state),
+            ElementState::empty()
+        );
+    }
+
+    #[test]
+    fn test_matches_state() {
+        let list = SelectorList::parse("a:hover").expect("failed to parse selector");
+        let mut a = element("a", None, &[]);
+        assert!(!list.matches(&a));
+
+        let old = a.state;
+        a.state.set(ElementState::HOVER, true);
+        assert!(list.depends_on_state(ElementState::changed(old, a.state)));
+        assert!(list.matches(&a));
+
+        a.state.set(ElementState::FOCUS, true);
+        assert!(!list.depends_on_state(ElementState::FOCUS));
+        assert!(
+            !SelectorList::parse("a.b")
+                .expect("failed to parse selector")
+                .depends_on_state(ElementState::HOVER)
+        );
+    }
+
+    #[test]
+    fn test_nth_parse() {
+        assert_eq!(Nth::parse("odd"), Some(Nth::new(2, 1)));
+        assert_eq!(Nth::parse(" EVEN "), Some(Nth::new(2, 0)));
+        assert_eq!(Nth::parse("3"), Some(Nth::new(0, 3)));
+        assert_eq!(Nth::parse("-2"), Some(Nth::new(0, -2)));
+        assert_eq!(Nth::parse("n"), Some(Nth::new(1, 0)));
+        assert_eq!(Nth::parse("+n+1"), Some(Nth::new(1, 1)));
+        assert_eq!(Nth::parse("-n+3"), Some(Nth::new(-1, 3)));
+        assert_eq!(Nth::parse("2n + 1"), Some(Nth::new(2, 1)));
+        assert_eq!(Nth::parse("3N- 2"), Some(Nth::new(3, -2)));
+        assert_eq!(Nth::parse("-4n"), Some(Nth::new(-4, 0)));
+        assert_eq!(Nth::parse(""), None);
+        assert_eq!(Nth::parse("+ n"), None);
+        assert_eq!(Nth::parse("2n+-1"), None);
+        assert_eq!(Nth::parse("2n 1"), None);
+        assert_eq!(Nth::parse("1.5n"), None);
+        assert_eq!(Nth::parse("foo"), None);
+    }
+
+    #[test]
+    fn test_nth_matches() {
+        let odd = Nth::new(2, 1);
+        assert!(odd.matches(1));
+        assert!(!odd.matches(2));
+        assert!(odd.matches(3));
+        let first_three = Nth::new(-1, 3);
+        assert!(first_three.matches(3));
+        assert!(first_three.matches(1));
+        assert!(!first_three.matches(4));
+        assert!(Nth::new(0, 2).matches(2));
+        assert!(!Nth::new(0, 2).matches(4));
+        assert!(!Nth::new(3, -2).matches(2));
+        assert!(Nth::new(3, -2).matches(4));
+        assert_eq!(Nth::new(-1, 3).to_string(), "-n+3");
+        assert_eq!(Nth::new(2, -1).to_string(), "2n-1");
+        assert_eq!(Nth::new(0, 5).to_string(), "5");
+    }
+
+    #[test]
+    fn test_structural_pseudo_classes() {
+        let list = SelectorList::parse("tr:nth-child(even), li:first-child, li:last-child")
+            .expect("failed to parse selector");
+        assert_eq!(
+            list.to_string(),
+            "tr:nth-child(2n), li:first-child, li:last-child"
+        );
+
+        let mut tr = element("tr", None, &[]);
+        tr.position = (2, 5);
+        assert!(list.matches(&tr));
+        tr.position = (3, 5);
+        assert!(!list.matches(&tr));
+
+        let mut li = element("li", None, &[]);
+        li.position = (1, 3);
+        assert!(list.matches(&li));
+        li.position = (2, 3);
+        assert!(!list.matches(&li));
+        li.position = (3, 3);
+        assert!(list.matches(&li));
+
+        let last_two =
+            CompoundSelector::parse(":nth-last-child(-n+2)").expect("failed to parse selector");
+        li.position = (2, 3);
+        assert!(last_two.matches(&li));
+        li.position = (1, 3);
+        assert!(!last_two.matches(&li));
+    }
+
+    #[test]
+    fn test_structural_pseudo_class_errors() {
+        assert_eq!(
+            SelectorList::parse("li:nth-child(foo)"),
+            Err(SelectorParseError::InvalidArgument(
+                "nth-child(foo)".to_string()
+            ))
+        );
+        assert_eq!(
+            SelectorList::parse("li:nth-child(2n"),
+            Err(SelectorParseError::InvalidArgument(
+                "nth-child(2n".to_string()
+            ))
+        );
+        assert_eq!(
+            SelectorList::parse("li:hover(1)"),
+            Err(SelectorParseError::UnknownPseudoClass(
+                "hover()".to_string()
+            ))
+        );
+        assert_eq!(
+            SelectorList::parse("li:nth-child"),
+            Err(SelectorParseError::UnknownPseudoClass(
+                "nth-child".to_string()
+            ))
+        );
+    }
+}
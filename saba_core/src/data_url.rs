@@ -0,0 +1,197 @@
+use crate::error::Error;
+use crate::url::Url;
+use crate::url::percent_decode;
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+/// data:[<mediatype>][;base64],<data> 形式のURLをデコードしたもの
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataUrl {
+    media_type: String,
+    base64: bool,
+    body: Vec<u8>,
+}
+
+impl DataUrl {
+    pub fn parse(url: &Url) -> Result<Self, Error> {
+        if url.scheme() != "data" {
+            return Err(Error::UnexpectedInput(format!(
+                "not a data URL: {}",
+                url.scheme()
+            )));
+        }
+
+        // "data:,a?b"の"?b"もデータの一部なので、クエリを戻しておく
+        let mut path = url.path();
+        if !url.searchpart().is_empty() {
+            path.push('?');
+            path.push_str(&url.searchpart());
+        }
+        let (media_type, data) = match path.split_once(',') {
+            Some((m, d)) => (m, d),
+            None => {
+                return Err(Error::UnexpectedInput(format!(
+                    "missing ',' in data URL: {}",
+                    path
+                )));
+            }
+        };
+
+        let mut media_type = media_type.trim_matches(|c: char| c.is_ascii_whitespace());
+        let mut base64 = false;
+        if let Some(index) = media_type.rfind(';')
+            && media_type[index + 1..]
+                .trim_start_matches(' ')
+                .eq_ignore_ascii_case("base64")
+        {
+            base64 = true;
+            media_type = &media_type[..index];
+        }
+
+        // メディアタイプが省略された場合はtext/plain;charset=US-ASCIIとみなす
+        let media_type = if media_type.is_empty() {
+            "text/plain;charset=US-ASCII".to_string()
+        } else if media_type.starts_with(';') {
+            format!("text/plain{}", media_type)
+        } else {
+            media_type.to_string()
+        };
+
+        let decoded = percent_decode(data);
+        let body = if base64 {
+            match decode_base64(&decoded) {
+                Some(body) => body,
+                None => {
+                    return Err(Error::UnexpectedInput(format!(
+                        "invalid base64 in data URL: {}",
+                        data
+                    )));
+                }
+            }
+        } else {
+            decoded
+        };
+
+        Ok(Self {
+            media_type,
+            base64,
+            body,
+        })
+    }
+
+    pub fn media_type(&self) -> String {
+        self.media_type.clone()
+    }
+    pub fn is_base64(&self) -> bool {
+        self.base64
+    }
+    pub fn body(&self) -> Vec<u8> {
+        self.body.clone()
+    }
+}
+
+/// https://infra.spec.whatwg.org/#forgiving-base64-decode
+fn decode_base64(input: &[u8]) -> Option<Vec<u8>> {
+    let mut data: Vec<u8> = input
+        .iter()
+        .copied()
+        .filter(|b| !b.is_ascii_whitespace())
+        .collect();
+    if data.len().is_multiple_of(4) {
+        if data.ends_with(b"==") {
+            data.truncate(data.len() - 2);
+        } else if data.ends_with(b"=") {
+            data.truncate(data.len() - 1);
+        }
+    }
+    if data.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut output = Vec::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for b in data {
+        let value = match b {
+            b'A'..=b'Z' => b - b'A',
+            b'a'..=b'z' => b - b'a' + 26,
+            b'0'..=b'9' => b - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Result<DataUrl, Error> {
+        let url = Url::parse(input).expect("failed to parse url");
+        DataUrl::parse(&url)
+    }
+
+    #[test]
+    fn test_plain_text() {
+        let data = parse("data:text/plain,hello").expect("failed to parse data url");
+        assert_eq!(data.media_type(), "text/plain");
+        assert!(!data.is_base64());
+        assert_eq!(data.body(), b"hello".to_vec());
+    }
+
+    #[test]
+    fn test_default_media_type() {
+        let data = parse("data:,hello%20world").expect("failed to parse data url");
+        assert_eq!(data.media_type(), "text/plain;charset=US-ASCII");
+        assert_eq!(data.body(), b"hello world".to_vec());
+    }
+
+    #[test]
+    fn test_question_mark_in_data() {
+        let data = parse("data:,a?b=c#d").expect("failed to parse data url");
+        assert_eq!(data.body(), b"a?b=c".to_vec());
+    }
+
+    #[test]
+    fn test_charset_only() {
+        let data = parse("data:;charset=utf-8,a").expect("failed to parse data url");
+        assert_eq!(data.media_type(), "text/plain;charset=utf-8");
+    }
+
+    #[test]
+    fn test_base64() {
+        let data =
+            parse("data:text/html;base64,PGgxPmhpPC9oMT4=").expect("failed to parse data url");
+        assert_eq!(data.media_type(), "text/html");
+        assert!(data.is_base64());
+        assert_eq!(data.body(), b"<h1>hi</h1>".to_vec());
+    }
+
+    #[test]
+    fn test_base64_without_padding() {
+        let data = parse("data:;BASE64,YWI").expect("failed to parse data url");
+        assert_eq!(data.body(), b"ab".to_vec());
+    }
+
+    #[test]
+    fn test_invalid_base64() {
+        assert!(parse("data:;base64,a").is_err());
+        assert!(parse("data:;base64,a*b=").is_err());
+    }
+
+    #[test]
+    fn test_missing_comma() {
+        assert!(parse("data:text/plain").is_err());
+    }
+}
@@ -2,6 +2,24 @@
 
 extern crate alloc;
 
+pub mod about;
+pub mod css;
+pub mod data_url;
 pub mod error;
 pub mod http;
+pub mod loader;
+pub mod scheme;
+pub mod settings;
 pub mod url;
+
+// パース結果などをUIスレッド以外でも扱えるよう、コアの型がSend + Syncであることをコンパイル時に保証する
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<error::Error>();
+    assert_send_sync::<http::Header>();
+    assert_send_sync::<http::HttpResponse>();
+    assert_send_sync::<loader::InMemoryLoader>();
+    assert_send_sync::<scheme::SchemeRegistry>();
+    assert_send_sync::<settings::Settings>();
+    assert_send_sync::<url::Url>();
+};
@@ -0,0 +1,110 @@
+use crate::error::Error;
+use crate::url::Host;
+use crate::url::Url;
+use crate::url::percent_decode;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+
+/// file:// のURLをHTTPクライアントを使わずに読み込むためのフック
+/// ディスクやメモリ上のファイルシステムなど、埋め込み先の環境ごとに実装する
+/// ローダーを持つ側もスレッド間で共有できるよう、Send + Syncを要求する
+pub trait LocalLoader: Send + Sync {
+    fn load(&self, url: &Url) -> Result<String, Error>;
+}
+
+/// メモリ上にファイルを保持するLocalLoader (テストや、ファイルシステムを持たない環境向け)
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryLoader {
+    files: BTreeMap<String, String>,
+}
+
+impl InMemoryLoader {
+    pub fn new() -> Self {
+        Self {
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// pathは"/index.html"のような絶対パスで指定する
+    pub fn insert(&mut self, path: String, content: String) {
+        self.files.insert(path, content);
+    }
+}
+
+impl LocalLoader for InMemoryLoader {
+    fn load(&self, url: &Url) -> Result<String, Error> {
+        if url.scheme() != "file" {
+            return Err(Error::UnexpectedInput(format!(
+                "not a file URL: {}",
+                url.scheme()
+            )));
+        }
+        // file://localhost/はパース時に空のホストになっている。他のホストのファイルは読めない
+        if url.typed_host() != Host::Domain(String::new()) {
+            return Err(Error::UnexpectedInput(format!(
+                "not a local file: {}",
+                url.host()
+            )));
+        }
+        // URLのパスはパーセントエンコードされているので、デコードしてから探す
+        let path = format!("/{}", String::from_utf8_lossy(&percent_decode(&url.path())));
+        match self.files.get(&path) {
+            Some(content) => Ok(content.clone()),
+            None => Err(Error::Other(format!("file not found: {}", path))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_in_memory_loader() {
+        let mut loader = InMemoryLoader::new();
+        loader.insert(
+            "/test.html".to_string(),
+            "<html><body>hello</body></html>".to_string(),
+        );
+        let url = Url::parse("file:///test.html").expect("failed to parse url");
+        assert_eq!(
+            loader.load(&url),
+            Ok("<html><body>hello</body></html>".to_string())
+        );
+    }
+
+    #[test]
+    fn test_in_memory_loader_percent_encoded_path() {
+        let mut loader = InMemoryLoader::new();
+        loader.insert("/my file.html".to_string(), "hello".to_string());
+        let url = Url::parse("file:///my file.html").expect("failed to parse url");
+        assert_eq!(url.path(), "my%20file.html");
+        assert_eq!(loader.load(&url), Ok("hello".to_string()));
+    }
+
+    #[test]
+    fn test_in_memory_loader_remote_host() {
+        let mut loader = InMemoryLoader::new();
+        loader.insert("/test.html".to_string(), "hello".to_string());
+        let url = Url::parse("file://evil-server/test.html").expect("failed to parse url");
+        assert!(loader.load(&url).is_err());
+        let url = Url::parse("file://localhost/test.html").expect("failed to parse url");
+        assert_eq!(loader.load(&url), Ok("hello".to_string()));
+    }
+
+    #[test]
+    fn test_in_memory_loader_not_found() {
+        let loader = InMemoryLoader::new();
+        let url = Url::parse("file:///missing.html").expect("failed to parse url");
+        assert!(loader.load(&url).is_err());
+    }
+
+    #[test]
+    fn test_in_memory_loader_http_url() {
+        let loader = InMemoryLoader::new();
+        let url = Url::parse("http://example.com/test.html").expect("failed to parse url");
+        assert!(loader.load(&url).is_err());
+    }
+}
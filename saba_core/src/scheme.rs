@@ -0,0 +1,157 @@
+use crate::error::Error;
+use crate::url::SUPPORTED_SCHEMES;
+use crate::url::Url;
+use crate::url::is_special_scheme;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// 埋め込み先が独自のスキーム(saba://settingsやresource://など)のページを返すためのフック
+/// SchemeRegistryごと他のスレッドへ渡せるよう、Send + Syncを要求する
+pub trait SchemeHandler: Send + Sync {
+    fn load(&self, url: &Url) -> Result<String, Error>;
+}
+
+/// 独自スキームとそのハンドラの対応表
+/// 独自スキームのURLはUrl::parseで特別でないスキームとしてパースされ、"saba://settings"ならホストは"settings"になる
+/// ナビゲーションの際は、ここに登録されたハンドラにページの読み込みを任せる
+#[derive(Default)]
+pub struct SchemeRegistry {
+    schemes: Vec<String>,
+    handlers: Vec<Box<dyn SchemeHandler>>,
+}
+
+impl SchemeRegistry {
+    pub fn new() -> Self {
+        Self {
+            schemes: Vec::new(),
+            handlers: Vec::new(),
+        }
+    }
+
+    pub fn register(&mut self, scheme: &str, handler: Box<dyn SchemeHandler>) -> Result<(), Error> {
+        let mut chars = scheme.chars();
+        let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.');
+        if !valid {
+            return Err(Error::UnexpectedInput(format!(
+                "invalid scheme name: {}",
+                scheme
+            )));
+        }
+
+        let scheme = scheme.to_ascii_lowercase();
+        if SUPPORTED_SCHEMES.contains(&scheme.as_str()) || is_special_scheme(&scheme) {
+            return Err(Error::UnexpectedInput(format!(
+                "{} is handled by the browser itself",
+                scheme
+            )));
+        }
+        if self.schemes.contains(&scheme) {
+            return Err(Error::UnexpectedInput(format!(
+                "{} is already registered",
+                scheme
+            )));
+        }
+        self.schemes.push(scheme);
+        self.handlers.push(handler);
+        Ok(())
+    }
+
+    /// スキーム名は大文字小文字を区別しない
+    pub fn is_registered(&self, scheme: &str) -> bool {
+        let scheme = scheme.to_ascii_lowercase();
+        self.schemes.contains(&scheme)
+    }
+
+    /// URLのスキームに対応するハンドラにページの読み込みを任せる
+    pub fn load(&self, url: &Url) -> Result<String, Error> {
+        match self.schemes.iter().position(|s| *s == url.scheme()) {
+            Some(index) => self.handlers[index].load(url),
+            None => Err(Error::UnexpectedInput(format!(
+                "no handler for scheme: {}",
+                url.scheme()
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    struct EchoHandler {
+        name: String,
+    }
+
+    impl SchemeHandler for EchoHandler {
+        fn load(&self, url: &Url) -> Result<String, Error> {
+            Ok(format!("{}:{}", self.name, url.path()))
+        }
+    }
+
+    fn echo(name: &str) -> Box<dyn SchemeHandler> {
+        Box::new(EchoHandler {
+            name: name.to_string(),
+        })
+    }
+
+    #[test]
+    fn test_register_and_parse() {
+        let mut registry = SchemeRegistry::new();
+        assert_eq!(registry.register("saba", echo("saba")), Ok(()));
+        assert!(registry.is_registered("saba"));
+        assert!(registry.is_registered("SABA"));
+        assert!(!registry.is_registered("resource"));
+
+        let url = Url::parse("SABA://settings?tab=1#top").expect("failed to parse url");
+        assert_eq!(url.scheme(), "saba");
+        assert_eq!(url.host(), "settings");
+        assert_eq!(url.path(), "");
+        assert_eq!(url.searchpart(), "tab=1");
+        assert_eq!(url.fragment(), "top");
+        assert_eq!(url.as_str(), "saba://settings?tab=1#top");
+    }
+
+    #[test]
+    fn test_dispatch() {
+        let mut registry = SchemeRegistry::new();
+        registry
+            .register("saba", echo("saba"))
+            .expect("failed to register");
+        registry
+            .register("resource", echo("resource"))
+            .expect("failed to register");
+
+        let url = Url::parse("resource://icons/logo.png").expect("failed to parse url");
+        assert_eq!(url.host(), "icons");
+        assert_eq!(registry.load(&url), Ok("resource:logo.png".to_string()));
+
+        let joined = url.join("../fonts/a.woff").expect("failed to join url");
+        assert_eq!(joined.as_str(), "resource://icons/fonts/a.woff");
+        assert_eq!(
+            registry.load(&joined),
+            Ok("resource:fonts/a.woff".to_string())
+        );
+
+        // パースはできても、ハンドラが登録されていなければ読み込めない
+        let url = Url::parse("gopher://example.com/").expect("failed to parse url");
+        assert!(registry.load(&url).is_err());
+        let url = Url::parse("http://example.com/").expect("failed to parse url");
+        assert!(registry.load(&url).is_err());
+    }
+
+    #[test]
+    fn test_register_invalid() {
+        let mut registry = SchemeRegistry::new();
+        assert!(registry.register("http", echo("http")).is_err());
+        assert!(registry.register("https", echo("https")).is_err());
+        assert!(registry.register("about", echo("about")).is_err());
+        assert!(registry.register("1abc", echo("1abc")).is_err());
+        assert!(registry.register("sa ba", echo("saba")).is_err());
+        assert!(registry.register("saba", echo("saba")).is_ok());
+        assert!(registry.register("SABA", echo("saba")).is_err());
+    }
+}
@@ -0,0 +1,351 @@
+use crate::error::Error;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingKey {
+    JavaScriptEnabled,
+    ImageLoadingEnabled,
+    UserAgent,
+    SearchTemplate,
+    CacheSize,
+    DefaultZoom,
+}
+
+impl SettingKey {
+    pub const ALL: [SettingKey; 6] = [
+        SettingKey::JavaScriptEnabled,
+        SettingKey::ImageLoadingEnabled,
+        SettingKey::UserAgent,
+        SettingKey::SearchTemplate,
+        SettingKey::CacheSize,
+        SettingKey::DefaultZoom,
+    ];
+
+    /// about:configに表示される名前
+    pub fn name(&self) -> &'static str {
+        match self {
+            SettingKey::JavaScriptEnabled => "javascript.enabled",
+            SettingKey::ImageLoadingEnabled => "image.loading.enabled",
+            SettingKey::UserAgent => "general.useragent",
+            SettingKey::SearchTemplate => "browser.search.template",
+            SettingKey::CacheSize => "browser.cache.size",
+            SettingKey::DefaultZoom => "layout.zoom.default",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|key| key.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingValue {
+    Bool(bool),
+    Integer(u64),
+    Text(String),
+}
+
+impl SettingValue {
+    fn type_name(&self) -> &'static str {
+        match self {
+            SettingValue::Bool(_) => "bool",
+            SettingValue::Integer(_) => "integer",
+            SettingValue::Text(_) => "string",
+        }
+    }
+}
+
+impl core::fmt::Display for SettingValue {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SettingValue::Bool(b) => write!(f, "{}", b),
+            SettingValue::Integer(n) => write!(f, "{}", n),
+            SettingValue::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+// Settingsを他のスレッドと共有できるよう、コールバックもSend + Syncにする
+type Observer = Box<dyn Fn(SettingKey, &SettingValue) + Send + Sync>;
+
+/// エンジン全体から参照される実行時の設定値
+pub struct Settings {
+    javascript_enabled: bool,
+    image_loading_enabled: bool,
+    user_agent: String,
+    search_template: String,
+    cache_size: u64,
+    default_zoom: u64,
+    observers: Vec<Observer>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Settings {
+    pub fn new() -> Self {
+        Self {
+            javascript_enabled: true,
+            image_loading_enabled: true,
+            user_agent: "saba/0.1.0".to_string(),
+            search_template: "http://www.google.com/search?q=%s".to_string(),
+            cache_size: 16 * 1024 * 1024,
+            default_zoom: 100,
+            observers: Vec::new(),
+        }
+    }
+
+    pub fn javascript_enabled(&self) -> bool {
+        self.javascript_enabled
+    }
+    pub fn image_loading_enabled(&self) -> bool {
+        self.image_loading_enabled
+    }
+    pub fn user_agent(&self) -> String {
+        self.user_agent.clone()
+    }
+    pub fn search_template(&self) -> String {
+        self.search_template.clone()
+    }
+    /// キャッシュの最大サイズ (バイト)
+    pub fn cache_size(&self) -> u64 {
+        self.cache_size
+    }
+    /// 既定の拡大率 (パーセント)
+    pub fn default_zoom(&self) -> u64 {
+        self.default_zoom
+    }
+
+    pub fn get(&self, key: SettingKey) -> SettingValue {
+        match key {
+            SettingKey::JavaScriptEnabled => SettingValue::Bool(self.javascript_enabled),
+            SettingKey::ImageLoadingEnabled => SettingValue::Bool(self.image_loading_enabled),
+            SettingKey::UserAgent => SettingValue::Text(self.user_agent.clone()),
+            SettingKey::SearchTemplate => SettingValue::Text(self.search_template.clone()),
+            SettingKey::CacheSize => SettingValue::Integer(self.cache_size),
+            SettingKey::DefaultZoom => SettingValue::Integer(self.default_zoom),
+        }
+    }
+
+    pub fn set(&mut self, key: SettingKey, value: SettingValue) -> Result<(), Error> {
+        match (key, &value) {
+            (SettingKey::JavaScriptEnabled, SettingValue::Bool(b)) => self.javascript_enabled = *b,
+            (SettingKey::ImageLoadingEnabled, SettingValue::Bool(b)) => {
+                self.image_loading_enabled = *b
+            }
+            (SettingKey::UserAgent, SettingValue::Text(s)) => self.user_agent = s.clone(),
+            (SettingKey::SearchTemplate, SettingValue::Text(s)) => {
+                if !s.contains("%s") {
+                    return Err(Error::UnexpectedInput(format!(
+                        "search template must contain %s: {}",
+                        s
+                    )));
+                }
+                self.search_template = s.clone()
+            }
+            (SettingKey::CacheSize, SettingValue::Integer(n)) => self.cache_size = *n,
+            (SettingKey::DefaultZoom, SettingValue::Integer(n)) => {
+                if *n == 0 {
+                    return Err(Error::UnexpectedInput(
+                        "default zoom must be greater than 0".to_string(),
+                    ));
+                }
+                self.default_zoom = *n
+            }
+            _ => {
+                return Err(Error::UnexpectedInput(format!(
+                    "{} does not accept a {} value",
+                    key.name(),
+                    value.type_name()
+                )));
+            }
+        }
+
+        for observer in &self.observers {
+            observer(key, &value);
+        }
+        Ok(())
+    }
+
+    /// about:configのように、名前と文字列で値を更新する
+    pub fn set_by_name(&mut self, name: &str, value: &str) -> Result<(), Error> {
+        let key = match SettingKey::from_name(name) {
+            Some(key) => key,
+            None => return Err(Error::UnexpectedInput(format!("unknown setting: {}", name))),
+        };
+        let value = match self.get(key) {
+            SettingValue::Bool(_) => match value {
+                "true" => SettingValue::Bool(true),
+                "false" => SettingValue::Bool(false),
+                _ => {
+                    return Err(Error::UnexpectedInput(format!(
+                        "{} expects true or false: {}",
+                        name, value
+                    )));
+                }
+            },
+            SettingValue::Integer(_) => match value.parse() {
+                Ok(n) => SettingValue::Integer(n),
+                Err(_) => {
+                    return Err(Error::UnexpectedInput(format!(
+                        "{} expects an integer: {}",
+                        name, value
+                    )));
+                }
+            },
+            SettingValue::Text(_) => SettingValue::Text(value.to_string()),
+        };
+        self.set(key, value)
+    }
+
+    /// 設定が変更されるたびに呼ばれるコールバックを登録する
+    pub fn add_observer(&mut self, observer: Observer) {
+        self.observers.push(observer);
+    }
+
+    /// about:configページのHTMLを生成する
+    pub fn about_config_html(&self) -> String {
+        self.about_config_html_with_errors(&[])
+    }
+
+    /// 設定の更新に失敗したときのエラーを表の上に表示する
+    pub fn about_config_html_with_errors(&self, errors: &[Error]) -> String {
+        let mut html = String::from("<html>\n<head><title>about:config</title></head>\n<body>\n");
+        html.push_str("<h1>about:config</h1>\n");
+        if !errors.is_empty() {
+            html.push_str("<ul>\n");
+            for error in errors {
+                let message = match error {
+                    Error::Network(m)
+                    | Error::UnexpectedInput(m)
+                    | Error::InvalidUI(m)
+                    | Error::Other(m) => m,
+                };
+                html.push_str(&format!("<li>{}</li>\n", escape_html(message)));
+            }
+            html.push_str("</ul>\n");
+        }
+        html.push_str("<table>\n");
+        for key in SettingKey::ALL {
+            let value = self.get(key);
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td><form action=\"about:config\"><input name=\"{}\" value=\"{}\"></form></td></tr>\n",
+                key.name(),
+                value.type_name(),
+                key.name(),
+                escape_html(&value.to_string())
+            ));
+        }
+        html.push_str("</table>\n</body>\n</html>\n");
+        html
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    let mut escaped = String::new();
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::sync::Arc;
+    use core::sync::atomic::AtomicUsize;
+    use core::sync::atomic::Ordering;
+
+    #[test]
+    fn test_defaults() {
+        let settings = Settings::new();
+        assert!(settings.javascript_enabled());
+        assert!(settings.image_loading_enabled());
+        assert_eq!(settings.default_zoom(), 100);
+        assert_eq!(
+            settings.get(SettingKey::UserAgent),
+            SettingValue::Text("saba/0.1.0".to_string())
+        );
+    }
+
+    #[test]
+    fn test_set() {
+        let mut settings = Settings::new();
+        assert_eq!(
+            settings.set(SettingKey::JavaScriptEnabled, SettingValue::Bool(false)),
+            Ok(())
+        );
+        assert!(!settings.javascript_enabled());
+    }
+
+    #[test]
+    fn test_set_type_mismatch() {
+        let mut settings = Settings::new();
+        assert!(
+            settings
+                .set(SettingKey::CacheSize, SettingValue::Bool(false))
+                .is_err()
+        );
+        assert_eq!(settings.cache_size(), 16 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_set_by_name() {
+        let mut settings = Settings::new();
+        assert_eq!(settings.set_by_name("layout.zoom.default", "150"), Ok(()));
+        assert_eq!(settings.default_zoom(), 150);
+        assert_eq!(
+            settings.set_by_name("image.loading.enabled", "false"),
+            Ok(())
+        );
+        assert!(!settings.image_loading_enabled());
+        assert!(settings.set_by_name("layout.zoom.default", "abc").is_err());
+        assert!(settings.set_by_name("no.such.setting", "1").is_err());
+    }
+
+    #[test]
+    fn test_observer() {
+        let mut settings = Settings::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let user_agent_changes = Arc::new(AtomicUsize::new(0));
+        let (c, u) = (calls.clone(), user_agent_changes.clone());
+        settings.add_observer(Box::new(move |key, value| {
+            c.fetch_add(1, Ordering::SeqCst);
+            if key == SettingKey::UserAgent
+                && *value == SettingValue::Text("test-agent".to_string())
+            {
+                u.fetch_add(1, Ordering::SeqCst);
+            }
+        }));
+        settings
+            .set_by_name("general.useragent", "test-agent")
+            .expect("failed to update setting");
+        assert!(settings.set_by_name("layout.zoom.default", "0").is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(user_agent_changes.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_about_config_html() {
+        let mut settings = Settings::new();
+        settings
+            .set_by_name("general.useragent", "<saba>")
+            .expect("failed to update setting");
+        let html = settings.about_config_html();
+        assert!(html.contains("<td>javascript.enabled</td><td>bool</td>"));
+        assert!(html.contains("value=\"&lt;saba&gt;\""));
+    }
+}